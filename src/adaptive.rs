@@ -0,0 +1,127 @@
+use crate::difficulty::{self, ComplexityBand, DifficultyProfile};
+use crate::game::GameDifficulty;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+// two levels per preset: the easier half of its problems and then the harder half
+pub const MAX_LEVEL: u32 = 8;
+// fast correct answers in a row it takes to go up a level
+const STEP_UP_STREAK: u32 = 2;
+
+// Staircase moves the level of a game type up after a streak of fast correct answers and down
+// after every miss, so it settles where the player gets about two out of three right in time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Staircase {
+    level: u32,
+    streak: u32,
+}
+
+impl Default for Staircase {
+    fn default() -> Self {
+        Self::at(1)
+    }
+}
+
+impl Staircase {
+    pub fn at(level: u32) -> Self {
+        Self {
+            level: level.clamp(1, MAX_LEVEL),
+            streak: 0,
+        }
+    }
+
+    // for_preset starts at the easier half of a preset
+    pub fn for_preset(difficulty: GameDifficulty) -> Self {
+        let index = difficulty::presets()
+            .iter()
+            .position(|preset| preset.difficulty == difficulty)
+            .unwrap_or(0);
+        Self::at(2 * index as u32 + 1)
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    // target_time is how fast a correct answer has to be to count, bigger numbers get longer
+    fn target_time(&self) -> Duration {
+        Duration::seconds(4 * self.level.div_ceil(2) as i64)
+    }
+
+    pub fn record(&mut self, correct: bool, latency: Duration) {
+        if !correct {
+            self.level = (self.level - 1).max(1);
+            self.streak = 0;
+        } else if latency > self.target_time() {
+            // right but slow, the level is about right
+            self.streak = 0;
+        } else {
+            self.streak += 1;
+            if self.streak >= STEP_UP_STREAK {
+                self.level = (self.level + 1).min(MAX_LEVEL);
+                self.streak = 0;
+            }
+        }
+    }
+
+    // profile is the preset with the digits of the level, odd levels ask for the easier
+    // problems of those digits and even levels for the harder ones
+    pub fn profile(&self) -> DifficultyProfile {
+        let presets = difficulty::presets();
+        let preset = &presets[(self.level as usize - 1) / 2];
+        let complexity = if self.level % 2 == 1 {
+            ComplexityBand { min: 0, max: 50 }
+        } else {
+            ComplexityBand { min: 50, max: 100 }
+        };
+        DifficultyProfile {
+            name: format!("Level {}", self.level),
+            complexity,
+            ..preset.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staircase_steps() {
+        let mut staircase = Staircase::default();
+        let fast = Duration::seconds(1);
+        staircase.record(true, fast);
+        assert_eq!(staircase.level(), 1);
+        staircase.record(true, fast);
+        assert_eq!(staircase.level(), 2);
+
+        // a slow answer breaks the streak
+        staircase.record(true, fast);
+        staircase.record(true, Duration::seconds(30));
+        staircase.record(true, fast);
+        assert_eq!(staircase.level(), 2);
+
+        staircase.record(false, fast);
+        staircase.record(false, fast);
+        assert_eq!(staircase.level(), 1);
+
+        let mut top = Staircase::at(20);
+        assert_eq!(top.level(), MAX_LEVEL);
+        top.record(true, fast);
+        top.record(true, fast);
+        assert_eq!(top.level(), MAX_LEVEL);
+    }
+
+    #[test]
+    fn test_level_profiles() {
+        let first = Staircase::at(1).profile();
+        assert_eq!(first.difficulty, GameDifficulty::Basic);
+        assert_eq!(first.complexity.max, 50);
+
+        let hard = Staircase::at(6).profile();
+        assert_eq!(hard.difficulty, GameDifficulty::Hard);
+        assert_eq!(hard.operand1.digit_count(), 3);
+        assert_eq!(hard.complexity.min, 50);
+        assert_eq!(Staircase::for_preset(GameDifficulty::Hard).level(), 5);
+    }
+}
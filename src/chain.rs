@@ -0,0 +1,265 @@
+use crate::expr::{BinaryOp, Expr};
+use crate::rng::RandomSource;
+
+const OPERATORS: [BinaryOp; 4] = [BinaryOp::Add, BinaryOp::Sub, BinaryOp::Mul, BinaryOp::Div];
+const MAX_ATTEMPTS: usize = 100;
+
+// Shape is the operator layout of a chain before the numbers are chosen. Numbers are filled in
+// evaluation order so every divisor can be picked from the divisors of its dividend.
+enum Shape {
+    Leaf,
+    Node(BinaryOp, Box<Shape>, Box<Shape>),
+    Paren(Box<Shape>),
+}
+
+// Limits bounds the numbers of a chain. Operands are drawn from min..=max, negated at random
+// when signed, and no intermediate result may be larger than bound in absolute value or, when
+// not signed, smaller than zero.
+struct Limits {
+    min: i32,
+    max: i32,
+    bound: i64,
+    signed: bool,
+}
+
+// generate_chain builds a chain like `12 + 7 * 3 - 40 / 5` with `operands` numbers drawn from
+// min..=max. Every intermediate result is a whole number no larger than max * max.
+pub fn generate_chain(
+    rng: &mut dyn RandomSource,
+    operands: usize,
+    min: i32,
+    max: i32,
+    parentheses: bool,
+    signed: bool,
+) -> Expr {
+    let operands = operands.max(2);
+    let limits = Limits {
+        min,
+        max,
+        bound: max as i64 * max as i64,
+        signed,
+    };
+
+    for _ in 0..MAX_ATTEMPTS {
+        let ops: Vec<BinaryOp> = (1..operands)
+            .map(|_| OPERATORS[rng.gen_range(0, OPERATORS.len() as i32 - 1) as usize])
+            .collect();
+        let shape = if parentheses && operands > 2 {
+            shape_with_parentheses(rng, ops)
+        } else {
+            combine((0..operands).map(|_| Shape::Leaf).collect(), &ops)
+        };
+
+        if let Some((expr, _)) = fill(&shape, rng, &limits) {
+            return expr;
+        }
+    }
+
+    // a plain sum always stays in bounds
+    let ops = vec![BinaryOp::Add; operands - 1];
+    let shape = combine((0..operands).map(|_| Shape::Leaf).collect(), &ops);
+    let unbounded = Limits {
+        bound: i64::MAX,
+        ..limits
+    };
+    fill(&shape, rng, &unbounded)
+        .expect("a sum of operands never overflows")
+        .0
+}
+
+// shape_with_parentheses groups a random span of at least two operands. The group always
+// contains an addition or subtraction, otherwise the parentheses would not change anything,
+// and it is never used as a divisor.
+fn shape_with_parentheses(rng: &mut dyn RandomSource, mut ops: Vec<BinaryOp>) -> Shape {
+    let operands = ops.len() + 1;
+    let len = rng.gen_range(2, operands as i32 - 1) as usize;
+    let start = rng.gen_range(0, (operands - len) as i32) as usize;
+
+    let inner_ops = &mut ops[start..start + len - 1];
+    if !inner_ops
+        .iter()
+        .any(|op| matches!(op, BinaryOp::Add | BinaryOp::Sub))
+    {
+        let i = rng.gen_range(0, inner_ops.len() as i32 - 1) as usize;
+        inner_ops[i] = if rng.gen_range(0, 1) == 0 {
+            BinaryOp::Add
+        } else {
+            BinaryOp::Sub
+        };
+    }
+    if start > 0 && ops[start - 1] == BinaryOp::Div {
+        ops[start - 1] = BinaryOp::Mul;
+    }
+
+    let inner = combine(
+        (0..len).map(|_| Shape::Leaf).collect(),
+        &ops[start..start + len - 1],
+    );
+
+    let mut atoms: Vec<Shape> = (0..start).map(|_| Shape::Leaf).collect();
+    atoms.push(Shape::Paren(Box::new(inner)));
+    atoms.extend((start + len..operands).map(|_| Shape::Leaf));
+
+    let mut outer_ops = ops[..start].to_vec();
+    outer_ops.extend_from_slice(&ops[start + len - 1..]);
+
+    combine(atoms, &outer_ops)
+}
+
+// combine applies operator precedence: multiplications and divisions bind first, then the
+// terms are added and subtracted from left to right.
+fn combine(atoms: Vec<Shape>, ops: &[BinaryOp]) -> Shape {
+    let mut atoms = atoms.into_iter();
+    let mut terms = vec![atoms.next().expect("a chain has at least one operand")];
+    let mut additive = Vec::new();
+
+    for (op, atom) in ops.iter().zip(atoms) {
+        match op {
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::DivRem => {
+                let left = terms.pop().expect("terms is never empty");
+                terms.push(Shape::Node(*op, Box::new(left), Box::new(atom)));
+            }
+            BinaryOp::Add | BinaryOp::Sub => {
+                additive.push(*op);
+                terms.push(atom);
+            }
+        }
+    }
+
+    let mut terms = terms.into_iter();
+    let mut shape = terms.next().expect("terms is never empty");
+    for (op, term) in additive.into_iter().zip(terms) {
+        shape = Shape::Node(op, Box::new(shape), Box::new(term));
+    }
+    shape
+}
+
+fn fill(shape: &Shape, rng: &mut dyn RandomSource, limits: &Limits) -> Option<(Expr, i64)> {
+    match shape {
+        Shape::Leaf => {
+            let mut n = rng.gen_range(limits.min, limits.max);
+            if limits.signed && rng.gen_range(0, 1) == 1 {
+                n = -n;
+            }
+            Some((Expr::number(n), n as i64))
+        }
+        Shape::Paren(inner) => {
+            let (expr, value) = fill(inner, rng, limits)?;
+            Some((Expr::Paren(Box::new(expr)), value))
+        }
+        Shape::Node(op, left, right) => {
+            let (left_expr, a) = fill(left, rng, limits)?;
+            let (right_expr, b) = match (op, right.as_ref()) {
+                (BinaryOp::Div, Shape::Leaf) => {
+                    let d = pick_divisor(rng, a, limits.max);
+                    (Expr::number(d), d as i64)
+                }
+                _ => fill(right, rng, limits)?,
+            };
+
+            let value = match op {
+                BinaryOp::Add => a.checked_add(b)?,
+                BinaryOp::Sub => a.checked_sub(b)?,
+                BinaryOp::Mul => a.checked_mul(b)?,
+                BinaryOp::Div => {
+                    if b == 0 || a % b != 0 {
+                        return None;
+                    }
+                    a / b
+                }
+                // chains only use exact division
+                BinaryOp::DivRem => return None,
+            };
+
+            if value.abs() > limits.bound || (!limits.signed && value < 0) {
+                return None;
+            }
+            Some((Expr::binary(*op, left_expr, right_expr), value))
+        }
+    }
+}
+
+// pick_divisor prefers a divisor other than 1 so the division is not trivial
+fn pick_divisor(rng: &mut dyn RandomSource, dividend: i64, max: i32) -> i32 {
+    if dividend == 0 {
+        return rng.gen_range(1, max.max(1));
+    }
+    let limit = dividend.abs().min(max as i64) as i32;
+    let divisors: Vec<i32> = (2..=limit).filter(|d| dividend % *d as i64 == 0).collect();
+    if divisors.is_empty() {
+        1
+    } else {
+        divisors[rng.gen_range(0, divisors.len() as i32 - 1) as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Answer;
+    use crate::rng::SeededRng;
+
+    #[test]
+    fn test_combine_respects_precedence() {
+        let mut rng = crate::rng::PairRng::new(|_, _| (2, 3));
+        // 2 + 3 * 2 - 3
+        let shape = combine(
+            (0..4).map(|_| Shape::Leaf).collect(),
+            &[BinaryOp::Add, BinaryOp::Mul, BinaryOp::Sub],
+        );
+        let limits = Limits {
+            min: 1,
+            max: 9,
+            bound: 81,
+            signed: false,
+        };
+        let (expr, value) = fill(&shape, &mut rng, &limits).unwrap();
+        assert_eq!(expr.to_string(), "2 + 3 * 2 - 3");
+        assert_eq!(value, 5);
+        assert_eq!(expr.evaluate(), Some(Answer::Integer(5)));
+    }
+
+    #[test]
+    fn test_chains_are_integral_and_bounded() {
+        let mut rng = SeededRng::new(3);
+        for operands in 2..=6 {
+            for parentheses in [false, true] {
+                for _ in 0..200 {
+                    let expr = generate_chain(&mut rng, operands, 1, 9, parentheses, false);
+                    assert_eq!(expr.operand_count(), operands, "{}", expr);
+                    match expr.evaluate() {
+                        Some(Answer::Integer(n)) => {
+                            assert!((0..=81).contains(&n), "{} = {}", expr, n)
+                        }
+                        other => panic!("{} evaluated to {:?}", expr, other),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parentheses_are_printed() {
+        let mut rng = SeededRng::new(11);
+        let with_parens = (0..50)
+            .map(|_| generate_chain(&mut rng, 4, 1, 9, true, false).to_string())
+            .filter(|text| text.contains('('))
+            .count();
+        assert_eq!(with_parens, 50);
+    }
+
+    #[test]
+    fn test_signed_chains() {
+        let mut rng = SeededRng::new(13);
+        let mut negative_operands = 0;
+        for _ in 0..200 {
+            let expr = generate_chain(&mut rng, 4, 1, 9, false, true);
+            negative_operands += expr.numbers().iter().filter(|n| **n < 0).count();
+            match expr.evaluate() {
+                Some(Answer::Integer(n)) => assert!((-81..=81).contains(&n), "{} = {}", expr, n),
+                other => panic!("{} evaluated to {:?}", expr, other),
+            }
+        }
+        assert!(negative_operands > 0);
+    }
+}
@@ -0,0 +1,385 @@
+use crate::decimal::Decimal;
+use crate::expr::{BinaryOp, Expr};
+use crate::game::Answer;
+use serde::{Deserialize, Serialize};
+use std::ops::AddAssign;
+
+// Complexity counts the mental steps of a problem the way it is worked out on paper: digit
+// columns, carries and borrows for sums and differences, partial products for products and
+// quotients, and whether fractions need a common denominator. 47 + 38 is two columns and a
+// carry while 50 + 50 is a single column.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Complexity {
+    pub columns: u32,
+    pub carries: u32,
+    pub borrows: u32,
+    pub partial_products: u32,
+    pub denominators: u32,
+    // the score the same operations would reach with the worst digits
+    max_score: u32,
+}
+
+impl Complexity {
+    pub fn of(expr: &Expr) -> Self {
+        match expr {
+            Expr::Number(_) | Expr::Fraction { .. } | Expr::Decimal(_) => Self::default(),
+            Expr::Paren(inner) | Expr::Hidden(inner) => Self::of(inner),
+            Expr::Binary(op, left, right) => {
+                let mut complexity = Self::of(left);
+                complexity += Self::of(right);
+                complexity += step(*op, left, right);
+                complexity
+            }
+        }
+    }
+
+    // of_missing scores a problem with a hidden operand by the steps that find it, the
+    // operations between the result and the unknown are undone: ? + 5 = 12 is worked out as
+    // 12 - 5. A division by the unknown or a quotient with a remainder is scored as written.
+    pub fn of_missing(expr: &Expr) -> Self {
+        expr.evaluate()
+            .and_then(literal)
+            .and_then(|result| undo(expr, result))
+            .unwrap_or_else(|| Self::of(expr))
+    }
+
+    pub fn score(&self) -> u32 {
+        self.columns + self.carries + self.borrows + self.partial_products + self.denominators
+    }
+
+    // percent compares the score with the worst case for the same operand sizes, so it can be
+    // compared between game types and difficulties
+    pub fn percent(&self) -> u32 {
+        (self.score() * 100)
+            .checked_div(self.max_score)
+            .unwrap_or(0)
+    }
+}
+
+impl AddAssign for Complexity {
+    fn add_assign(&mut self, other: Self) {
+        self.columns += other.columns;
+        self.carries += other.carries;
+        self.borrows += other.borrows;
+        self.partial_products += other.partial_products;
+        self.denominators += other.denominators;
+        self.max_score += other.max_score;
+    }
+}
+
+// undo works from the target value down to the hidden operand, the known side of every step is
+// worked out as usual
+fn undo(expr: &Expr, target: Expr) -> Option<Complexity> {
+    match expr {
+        Expr::Hidden(_) => Some(Complexity::default()),
+        Expr::Number(_) | Expr::Fraction { .. } | Expr::Decimal(_) => None,
+        Expr::Paren(inner) => undo(inner, target),
+        Expr::Binary(op, left, right) => {
+            let hidden_left = has_hidden(left);
+            let (hidden, known) = if hidden_left {
+                (left, right)
+            } else {
+                (right, left)
+            };
+            let value = literal(known.evaluate()?)?;
+            let inverse = match (op, hidden_left) {
+                (BinaryOp::Add, _) => Expr::binary(BinaryOp::Sub, target, value),
+                (BinaryOp::Sub, true) => Expr::binary(BinaryOp::Add, target, value),
+                (BinaryOp::Sub, false) => Expr::binary(BinaryOp::Sub, value, target),
+                (BinaryOp::Mul, _) => Expr::binary(BinaryOp::Div, target, value),
+                (BinaryOp::Div, true) => Expr::binary(BinaryOp::Mul, target, value),
+                (BinaryOp::Div, false) | (BinaryOp::DivRem, _) => return None,
+            };
+            let mut complexity = Complexity::of(known);
+            complexity += Complexity::of(&inverse);
+            complexity += undo(hidden, literal(inverse.evaluate()?)?)?;
+            Some(complexity)
+        }
+    }
+}
+
+fn has_hidden(expr: &Expr) -> bool {
+    match expr {
+        Expr::Hidden(_) => true,
+        Expr::Number(_) | Expr::Fraction { .. } | Expr::Decimal(_) => false,
+        Expr::Paren(inner) => has_hidden(inner),
+        Expr::Binary(_, left, right) => has_hidden(left) || has_hidden(right),
+    }
+}
+
+// literal writes a value back as an operand
+fn literal(answer: Answer) -> Option<Expr> {
+    match answer {
+        Answer::Integer(n) => Some(Expr::number(n)),
+        Answer::Fraction(r) => Some(Expr::fraction(
+            i32::try_from(r.numerator()).ok()?,
+            i32::try_from(r.denominator()).ok()?,
+        )),
+        Answer::Decimal(d) => Some(Expr::Decimal(d)),
+        Answer::QuotientRemainder { .. } => None,
+    }
+}
+
+// step scores one operation from the values of its operands
+fn step(op: BinaryOp, left: &Expr, right: &Expr) -> Complexity {
+    let (Some(a), Some(b)) = (left.evaluate(), right.evaluate()) else {
+        return Complexity::default();
+    };
+    match (a, b) {
+        (Answer::Integer(a), Answer::Integer(b)) => {
+            let (a, b) = (a as i64, b as i64);
+            match op {
+                BinaryOp::Add => signed_sum(a, b),
+                BinaryOp::Sub => signed_sum(a, -b),
+                BinaryOp::Mul => product(a, b),
+                // dividing is checked by multiplying the quotient back
+                BinaryOp::Div | BinaryOp::DivRem if b != 0 => product(a.div_euclid(b), b),
+                _ => Complexity::default(),
+            }
+        }
+        (Answer::Decimal(x), Answer::Decimal(y)) => {
+            let scale = x.scale().max(y.scale());
+            let aligned = |d: Decimal| d.mantissa() * 10i64.pow(scale - d.scale());
+            match op {
+                BinaryOp::Add => signed_sum(aligned(x), aligned(y)),
+                BinaryOp::Sub => signed_sum(aligned(x), -aligned(y)),
+                BinaryOp::Mul => product(x.mantissa(), y.mantissa()),
+                BinaryOp::Div => match x.checked_div(y) {
+                    Some(quotient) => product(quotient.mantissa(), y.mantissa()),
+                    None => Complexity::default(),
+                },
+                BinaryOp::DivRem => Complexity::default(),
+            }
+        }
+        _ => match (fraction_parts(left), fraction_parts(right)) {
+            (Some(x), Some(y)) => fraction_step(op, x, y),
+            _ => Complexity::default(),
+        },
+    }
+}
+
+// fraction_step counts the multiplications of the usual method, a sum of two fractions with
+// different denominators cross multiplies, a sum of like fractions just adds the numerators
+fn fraction_step(op: BinaryOp, (a, b): (i64, i64), (c, d): (i64, i64)) -> Complexity {
+    let mut complexity = Complexity {
+        max_score: 1,
+        ..Default::default()
+    };
+    match op {
+        BinaryOp::Add | BinaryOp::Sub if b == d => {
+            let c = if op == BinaryOp::Sub { -c } else { c };
+            complexity += signed_sum(a, c);
+        }
+        BinaryOp::Add | BinaryOp::Sub => {
+            complexity.denominators = 1;
+            complexity += product(a, d);
+            complexity += product(c, b);
+            complexity += product(b, d);
+        }
+        BinaryOp::Mul => {
+            complexity += product(a, c);
+            complexity += product(b, d);
+        }
+        BinaryOp::Div | BinaryOp::DivRem => {
+            complexity += product(a, d);
+            complexity += product(b, c);
+        }
+    }
+    complexity
+}
+
+// fraction_parts reads a fraction as written, 2/4 stays 2/4, whole numbers are over 1
+fn fraction_parts(expr: &Expr) -> Option<(i64, i64)> {
+    match expr {
+        Expr::Fraction {
+            numerator,
+            denominator,
+        } => Some((*numerator as i64, *denominator as i64)),
+        Expr::Paren(inner) | Expr::Hidden(inner) => fraction_parts(inner),
+        _ => match expr.evaluate()? {
+            Answer::Integer(n) => Some((n as i64, 1)),
+            Answer::Fraction(r) => Some((r.numerator(), r.denominator())),
+            _ => None,
+        },
+    }
+}
+
+fn digits(n: u64) -> Vec<u64> {
+    let mut digits = Vec::new();
+    let mut n = n;
+    while n > 0 {
+        digits.push(n % 10);
+        n /= 10;
+    }
+    digits
+}
+
+// signed_sum adds two numbers the way it is done by hand: with equal signs the sizes are
+// added, otherwise the smaller size is subtracted from the larger one
+pub fn signed_sum(a: i64, b: i64) -> Complexity {
+    let (x, y) = (a.unsigned_abs(), b.unsigned_abs());
+    if (a < 0) == (b < 0) {
+        column_addition(x, y)
+    } else {
+        column_subtraction(x.max(y), x.min(y))
+    }
+}
+
+// column_addition counts the columns with something to add and the carries out of them
+pub fn column_addition(a: u64, b: u64) -> Complexity {
+    let (a, b) = (digits(a), digits(b));
+    let width = a.len().max(b.len());
+    let mut complexity = Complexity {
+        max_score: 2 * width as u32,
+        ..Default::default()
+    };
+    let mut carry = 0;
+    for i in 0..width {
+        let (x, y) = (*a.get(i).unwrap_or(&0), *b.get(i).unwrap_or(&0));
+        // a column is work when at least two of the digits and the carry are not zero
+        if [x, y, carry].iter().filter(|d| **d > 0).count() >= 2 {
+            complexity.columns += 1;
+        }
+        carry = (x + y + carry) / 10;
+        complexity.carries += carry as u32;
+    }
+    complexity
+}
+
+// column_subtraction counts the columns with something to subtract and the borrows, a must
+// not be smaller than b
+pub fn column_subtraction(a: u64, b: u64) -> Complexity {
+    let (a, b) = (digits(a), digits(b));
+    let mut complexity = Complexity {
+        max_score: 2 * a.len() as u32,
+        ..Default::default()
+    };
+    let mut borrow = 0;
+    for (i, x) in a.iter().enumerate() {
+        let y = *b.get(i).unwrap_or(&0);
+        if y > 0 || borrow > 0 {
+            complexity.columns += 1;
+        }
+        let needs = y + borrow > *x;
+        complexity.borrows += needs as u32;
+        borrow = needs as u64;
+    }
+    complexity
+}
+
+// product counts the single digit products of long multiplication and the ones that carry
+fn product(a: i64, b: i64) -> Complexity {
+    let (a, b) = (digits(a.unsigned_abs()), digits(b.unsigned_abs()));
+    let mut complexity = Complexity {
+        max_score: 2 * (a.len() * b.len()) as u32,
+        ..Default::default()
+    };
+    for x in a.iter().filter(|x| **x > 0) {
+        for y in b.iter().filter(|y| **y > 0) {
+            complexity.partial_products += 1;
+            if x * y >= 10 {
+                complexity.carries += 1;
+            }
+        }
+    }
+    complexity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn of(op: BinaryOp, a: i32, b: i32) -> Complexity {
+        Complexity::of(&Expr::binary(op, Expr::number(a), Expr::number(b)))
+    }
+
+    #[test]
+    fn test_carries_make_sums_harder() {
+        let easy = of(BinaryOp::Add, 50, 50);
+        let hard = of(BinaryOp::Add, 47, 38);
+        assert_eq!((easy.columns, easy.carries), (1, 1));
+        assert_eq!((hard.columns, hard.carries), (2, 1));
+        assert!(easy.percent() < hard.percent());
+        assert_eq!(of(BinaryOp::Add, 999, 1).carries, 3);
+    }
+
+    #[test]
+    fn test_missing_operands() {
+        let hidden = |n| Expr::Hidden(Box::new(Expr::number(n)));
+        // ? + 5 = 12 is 12 - 5, a borrow where 7 + 5 has a carry
+        let sum = Expr::binary(BinaryOp::Add, hidden(7), Expr::number(5));
+        assert_eq!(Complexity::of_missing(&sum), of(BinaryOp::Sub, 12, 5));
+        assert_ne!(Complexity::of_missing(&sum), of(BinaryOp::Add, 7, 5));
+        // 20 - ? = 13 is 20 - 13
+        let difference = Expr::binary(BinaryOp::Sub, Expr::number(20), hidden(7));
+        assert_eq!(
+            Complexity::of_missing(&difference),
+            of(BinaryOp::Sub, 20, 13)
+        );
+        // ? * 6 = 42 is 42 / 6, which is checked as 7 * 6 like every division
+        let product = Expr::binary(BinaryOp::Mul, hidden(7), Expr::number(6));
+        assert_eq!(Complexity::of_missing(&product), of(BinaryOp::Div, 42, 6));
+        // a chain undoes the step around the unknown too, 3 + ? * 4 = 23 is (23 - 3) / 4
+        let chain = Expr::binary(
+            BinaryOp::Add,
+            Expr::number(3),
+            Expr::binary(BinaryOp::Mul, hidden(5), Expr::number(4)),
+        );
+        let mut steps = of(BinaryOp::Sub, 23, 3);
+        steps += of(BinaryOp::Div, 20, 4);
+        assert_eq!(Complexity::of_missing(&chain), steps);
+    }
+
+    #[test]
+    fn test_borrows() {
+        let complexity = of(BinaryOp::Sub, 402, 178);
+        assert_eq!((complexity.columns, complexity.borrows), (3, 2));
+        assert_eq!(of(BinaryOp::Sub, 58, 23).borrows, 0);
+        // 5 - 9 is worked out as 9 - 5
+        assert_eq!(of(BinaryOp::Sub, 5, 9).borrows, 0);
+        // 12 + -5 is a subtraction
+        assert_eq!(of(BinaryOp::Add, 12, -5).borrows, 1);
+    }
+
+    #[test]
+    fn test_partial_products() {
+        let complexity = of(BinaryOp::Mul, 23, 45);
+        assert_eq!(complexity.partial_products, 4);
+        assert_eq!(complexity.carries, 3);
+        assert_eq!(of(BinaryOp::Mul, 20, 40).partial_products, 1);
+        // 56 / 7 is checked as 8 * 7
+        assert_eq!(of(BinaryOp::Div, 56, 7), of(BinaryOp::Mul, 8, 7));
+    }
+
+    #[test]
+    fn test_fraction_denominators() {
+        let like = Complexity::of(&Expr::binary(
+            BinaryOp::Add,
+            Expr::fraction(1, 5),
+            Expr::fraction(2, 5),
+        ));
+        let unlike = Complexity::of(&Expr::binary(
+            BinaryOp::Add,
+            Expr::fraction(1, 3),
+            Expr::fraction(1, 4),
+        ));
+        assert_eq!(like.denominators, 0);
+        assert_eq!(unlike.denominators, 1);
+        assert_eq!(unlike.partial_products, 3);
+        assert!(like.score() < unlike.score());
+    }
+
+    #[test]
+    fn test_chain_adds_up_its_steps() {
+        // 12 + 9 * 3
+        let expr = Expr::binary(
+            BinaryOp::Add,
+            Expr::number(12),
+            Expr::binary(BinaryOp::Mul, Expr::number(9), Expr::number(3)),
+        );
+        let complexity = Complexity::of(&expr);
+        assert_eq!(complexity.partial_products, 1);
+        assert_eq!(complexity.columns, 2);
+        assert_eq!(complexity.carries, 1);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::rational::Rational;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+const MAX_SCALE: u32 = 18;
+
+// Decimal is an exact fixed-point number: mantissa / 10^scale. Trailing zeros are always
+// stripped so 0.5 and 0.50 are the same struct, and equality is equality of value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: i64,
+    scale: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseDecimalError {
+    Empty,
+    InvalidNumber,
+    TooManyDigits,
+}
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDecimalError::Empty => write!(f, "no number given"),
+            ParseDecimalError::InvalidNumber => write!(f, "not a number"),
+            ParseDecimalError::TooManyDigits => write!(f, "too many digits"),
+        }
+    }
+}
+
+impl Decimal {
+    pub fn new(mantissa: i64, scale: u32) -> Option<Self> {
+        Self::from_wide(mantissa as i128, scale)
+    }
+
+    fn from_wide(mut mantissa: i128, mut scale: u32) -> Option<Self> {
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        if scale > MAX_SCALE {
+            return None;
+        }
+        Some(Self {
+            mantissa: i64::try_from(mantissa).ok()?,
+            scale,
+        })
+    }
+
+    pub fn mantissa(&self) -> i64 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    // aligned returns both mantissas at the larger of the two scales
+    fn aligned(self, other: Self) -> Option<(i128, i128, u32)> {
+        let scale = self.scale.max(other.scale);
+        let a = self.mantissa as i128 * 10i128.checked_pow(scale - self.scale)?;
+        let b = other.mantissa as i128 * 10i128.checked_pow(scale - other.scale)?;
+        Some((a, b, scale))
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let (a, b, scale) = self.aligned(other)?;
+        Self::from_wide(a.checked_add(b)?, scale)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let (a, b, scale) = self.aligned(other)?;
+        Self::from_wide(a.checked_sub(b)?, scale)
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let mantissa = (self.mantissa as i128).checked_mul(other.mantissa as i128)?;
+        Self::from_wide(mantissa, self.scale + other.scale)
+    }
+
+    // checked_div returns None when dividing by zero or when the quotient does not terminate
+    // within the supported number of decimal places
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        if other.mantissa == 0 {
+            return None;
+        }
+        let (a, b, _) = self.aligned(other)?;
+        for scale in 0..=MAX_SCALE {
+            let dividend = a.checked_mul(10i128.pow(scale))?;
+            if dividend % b == 0 {
+                return Self::from_wide(dividend / b, scale);
+            }
+        }
+        None
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Self {
+            mantissa: self.mantissa.checked_neg()?,
+            scale: self.scale,
+        })
+    }
+
+    pub fn to_rational(self) -> Option<Rational> {
+        Rational::new(self.mantissa, 10i64.checked_pow(self.scale)?)
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(n: i64) -> Self {
+        Self {
+            mantissa: n,
+            scale: 0,
+        }
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.aligned(*other) {
+            Some((a, b, _)) => a.cmp(&b),
+            // the scales are capped so aligning can't overflow i128
+            None => unreachable!("decimal scales are at most {}", MAX_SCALE),
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.scale == 0 {
+            return write!(f, "{}", self.mantissa);
+        }
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let digits = format!(
+            "{:0>width$}",
+            self.mantissa.unsigned_abs(),
+            width = self.scale as usize + 1
+        );
+        let (int, frac) = digits.split_at(digits.len() - self.scale as usize);
+        write!(f, "{}{}.{}", sign, int, frac)
+    }
+}
+
+// Parses "0.5", ".5", "0.50", "-.5" and "5"
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseDecimalError::Empty);
+        }
+
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if (int.is_empty() && frac.is_empty()) || !all_digits(int) || !all_digits(frac) {
+            return Err(ParseDecimalError::InvalidNumber);
+        }
+
+        let scale = frac.len() as u32;
+        let mut mantissa: i128 = 0;
+        for c in int.chars().chain(frac.chars()) {
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add(c.to_digit(10).unwrap_or(0) as i128))
+                .ok_or(ParseDecimalError::TooManyDigits)?;
+        }
+        if negative {
+            mantissa = -mantissa;
+        }
+        Self::from_wide(mantissa, scale).ok_or(ParseDecimalError::TooManyDigits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_equivalent_forms() {
+        assert_eq!(d("0.5"), d(".5"));
+        assert_eq!(d("0.5"), d("0.50"));
+        assert_eq!(d("2"), d("2.0"));
+        assert_eq!(d("-.25"), Decimal::new(-25, 2).unwrap());
+        assert_eq!(d(" 3. "), d("3"));
+        assert_eq!("".parse::<Decimal>(), Err(ParseDecimalError::Empty));
+        assert_eq!(
+            ".".parse::<Decimal>(),
+            Err(ParseDecimalError::InvalidNumber)
+        );
+        assert_eq!(
+            "1.2.3".parse::<Decimal>(),
+            Err(ParseDecimalError::InvalidNumber)
+        );
+        assert_eq!(
+            "1e3".parse::<Decimal>(),
+            Err(ParseDecimalError::InvalidNumber)
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(d("0.50").to_string(), "0.5");
+        assert_eq!(d("-0.05").to_string(), "-0.05");
+        assert_eq!(d("12.340").to_string(), "12.34");
+        assert_eq!(d("7").to_string(), "7");
+    }
+
+    #[test]
+    fn test_exact_arithmetic() {
+        // 0.1 + 0.2 is exactly 0.3, unlike f64
+        assert_eq!(d("0.1").checked_add(d("0.2")), Some(d("0.3")));
+        assert_eq!(d("5.2").checked_sub(d("7.25")), Some(d("-2.05")));
+        assert_eq!(d("3.7").checked_mul(d("5.2")), Some(d("19.24")));
+        assert_eq!(d("19.24").checked_div(d("5.2")), Some(d("3.7")));
+        assert_eq!(d("1").checked_div(d("8")), Some(d("0.125")));
+        assert_eq!(d("1").checked_div(d("3")), None);
+        assert_eq!(d("1").checked_div(d("0")), None);
+    }
+
+    #[test]
+    fn test_order_is_by_value() {
+        assert!(d("0.5") > d("0.49"));
+        assert!(d("-1.5") < d("-1.25"));
+        assert!(d("10") > d("9.99"));
+    }
+
+    #[test]
+    fn test_to_rational() {
+        assert_eq!(d("0.75").to_rational(), Rational::new(3, 4));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::difficulty::OperandRange;
+use crate::game::GameType;
+use crate::rng::{RandomSource, SeededRng};
+use serde::{Deserialize, Serialize};
+
+// larger decks are not worth going through one by one
+pub const MAX_DECK_SIZE: usize = 10_000;
+
+// facts lists every pair of operands of a game type in the order the generators use them, a
+// division is the quotient and the divisor. None for game types with too many or endless facts.
+pub fn facts(
+    game_type: GameType,
+    first: OperandRange,
+    second: OperandRange,
+) -> Option<Vec<(i32, i32)>> {
+    if !matches!(
+        game_type,
+        GameType::Addition | GameType::Subtraction | GameType::Multiplication | GameType::Division
+    ) {
+        return None;
+    }
+    let size = (first.max - first.min + 1) as usize * (second.max - second.min + 1) as usize;
+    if size > MAX_DECK_SIZE {
+        return None;
+    }
+    Some(
+        (first.min..=first.max)
+            .flat_map(|a| (second.min..=second.max).map(move |b| (a, b)))
+            .collect(),
+    )
+}
+
+// Deck deals facts without replacement. Only the shuffle seed and the position are saved, the
+// order is the same every time it is shuffled from the seed, so the deck can be saved cheaply.
+// The order is shuffled once per pass through the deck.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deck {
+    seed: u64,
+    position: usize,
+    #[serde(skip)]
+    order: Vec<(i32, i32)>,
+}
+
+// two decks are the same when they deal the same, whether the order is shuffled yet or not
+impl PartialEq for Deck {
+    fn eq(&self, other: &Self) -> bool {
+        (self.seed, self.position) == (other.seed, other.position)
+    }
+}
+
+impl Deck {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            position: 0,
+            order: Vec::new(),
+        }
+    }
+
+    // position is how many facts of this pass were answered
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // deal returns the fact on top of the deck, it stays there until it is answered. A used up
+    // deck is shuffled again in a new order.
+    pub fn deal(&mut self, facts: &[(i32, i32)]) -> Option<(i32, i32)> {
+        if facts.is_empty() {
+            return None;
+        }
+        if self.position >= facts.len() {
+            self.seed = self.seed.wrapping_add(1);
+            self.position = 0;
+            self.order.clear();
+        }
+        if self.order.len() != facts.len() {
+            self.order = shuffled(facts, self.seed);
+        }
+        Some(self.order[self.position])
+    }
+
+    // answered takes the fact on top off the deck
+    pub fn answered(&mut self) {
+        self.position += 1;
+    }
+}
+
+// shuffled is a Fisher-Yates shuffle driven by the seed
+fn shuffled(facts: &[(i32, i32)], seed: u64) -> Vec<(i32, i32)> {
+    let mut rng = SeededRng::new(seed);
+    let mut facts = facts.to_vec();
+    for i in (1..facts.len()).rev() {
+        let j = rng.gen_range(0, i as i32) as usize;
+        facts.swap(i, j);
+    }
+    facts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_facts() {
+        let digit = OperandRange::digits(1);
+        assert_eq!(facts(GameType::Addition, digit, digit).unwrap().len(), 81);
+        assert_eq!(
+            facts(GameType::Multiplication, OperandRange::digits(2), digit)
+                .unwrap()
+                .len(),
+            810
+        );
+        let big = OperandRange::digits(3);
+        assert_eq!(facts(GameType::Multiplication, big, big), None);
+        assert_eq!(facts(GameType::FractionAddition, digit, digit), None);
+    }
+
+    #[test]
+    fn test_deck_deals_every_fact_once() {
+        let digit = OperandRange::digits(1);
+        let facts = facts(GameType::Multiplication, digit, digit).unwrap();
+        let mut deck = Deck::new(5);
+
+        let deal = |deck: &mut Deck| {
+            let card = deck.deal(&facts).unwrap();
+            deck.answered();
+            card
+        };
+        let first: Vec<(i32, i32)> = (0..81).map(|_| deal(&mut deck)).collect();
+        let dealt: HashSet<(i32, i32)> = first.iter().copied().collect();
+        assert_eq!(dealt, facts.iter().copied().collect());
+        assert_ne!(first, facts, "the deck is shuffled");
+
+        // the next pass is another full deck in a new order
+        let second: Vec<(i32, i32)> = (0..81).map(|_| deal(&mut deck)).collect();
+        assert_eq!(second.iter().copied().collect::<HashSet<_>>(), dealt);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_deck_resumes_from_position() {
+        let digit = OperandRange::digits(1);
+        let facts = facts(GameType::Addition, digit, digit).unwrap();
+        let mut deck = Deck::new(9);
+        for _ in 0..30 {
+            deck.deal(&facts);
+            deck.answered();
+        }
+
+        let mut saved = Deck {
+            order: Vec::new(),
+            ..deck.clone()
+        };
+        assert_eq!(saved.position(), 30);
+        assert_eq!(saved.deal(&facts), deck.deal(&facts));
+    }
+
+    #[test]
+    fn test_unanswered_fact_stays_on_top() {
+        let digit = OperandRange::digits(1);
+        let facts = facts(GameType::Addition, digit, digit).unwrap();
+        let mut deck = Deck::new(2);
+        let top = deck.deal(&facts);
+        assert_eq!(deck.deal(&facts), top);
+        assert_eq!(deck.position(), 0);
+        deck.answered();
+        assert_ne!(deck.deal(&facts), top);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::complexity::Complexity;
+use crate::game::GameDifficulty;
+use serde::{Deserialize, Serialize};
+
+// operands are capped at four digits so products and decimal mantissas stay within i32
+pub const MAX_OPERAND: i32 = 9999;
+pub const MAX_DIGITS: u32 = 4;
+
+// OperandRange is the inclusive range one operand is drawn from. The minimum is at least 1 so
+// divisors and denominators are never zero. Saved ranges go through new as well, a hand edited
+// one could be anything.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "SavedRange")]
+pub struct OperandRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+#[derive(Deserialize)]
+struct SavedRange {
+    min: i32,
+    max: i32,
+}
+
+impl From<SavedRange> for OperandRange {
+    fn from(saved: SavedRange) -> Self {
+        Self::new(saved.min, saved.max)
+    }
+}
+
+impl OperandRange {
+    pub fn new(min: i32, max: i32) -> Self {
+        let (min, max) = (min.clamp(1, MAX_OPERAND), max.clamp(1, MAX_OPERAND));
+        Self {
+            min: min.min(max),
+            max: min.max(max),
+        }
+    }
+
+    // digits is the range of all numbers with exactly that many digits, 2 is 10..=99
+    pub fn digits(digits: u32) -> Self {
+        let digits = digits.clamp(1, MAX_DIGITS);
+        let low = 10i32.pow(digits - 1);
+        Self::new(low, low * 10 - 1)
+    }
+
+    // digit_count is the number of digits of the largest operand
+    pub fn digit_count(&self) -> u32 {
+        self.max.ilog10() + 1
+    }
+}
+
+// ComplexityBand is the range of Complexity::percent a difficulty asks for, so harder levels
+// skip problems like 30 + 40 or 500 + 500 that are easy for their size
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComplexityBand {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Default for ComplexityBand {
+    fn default() -> Self {
+        Self { min: 0, max: 100 }
+    }
+}
+
+impl ComplexityBand {
+    pub fn contains(&self, complexity: &Complexity) -> bool {
+        (self.min..=self.max).contains(&complexity.percent())
+    }
+}
+
+// DifficultyProfile decides the operands of every problem at a difficulty. The first operand is
+// the left number, the quotient of a division and the numerators of fractions, the second one
+// is the right number, the divisor and the denominators.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyProfile {
+    pub difficulty: GameDifficulty,
+    pub name: String,
+    pub operand1: OperandRange,
+    pub operand2: OperandRange,
+    #[serde(default)]
+    pub complexity: ComplexityBand,
+}
+
+impl DifficultyProfile {
+    // span covers both operands, used where numbers are not tied to a side like chains
+    pub fn span(&self) -> OperandRange {
+        OperandRange::new(
+            self.operand1.min.min(self.operand2.min),
+            self.operand1.max.max(self.operand2.max),
+        )
+    }
+}
+
+// presets are the built-in levels, every operand has the same number of digits and the
+// higher levels ask for more of the carries and partial products their numbers allow
+pub fn presets() -> Vec<DifficultyProfile> {
+    [
+        (GameDifficulty::Basic, "Basic", 1, 0),
+        (GameDifficulty::Medium, "Medium", 2, 40),
+        (GameDifficulty::Hard, "Hard", 3, 50),
+        (GameDifficulty::Mastery, "Mastery", 4, 60),
+    ]
+    .into_iter()
+    .map(
+        |(difficulty, name, digits, min_complexity)| DifficultyProfile {
+            difficulty,
+            name: name.to_string(),
+            operand1: OperandRange::digits(digits),
+            operand2: OperandRange::digits(digits),
+            complexity: ComplexityBand {
+                min: min_complexity,
+                max: 100,
+            },
+        },
+    )
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::{BinaryOp, Expr};
+
+    #[test]
+    fn test_presets() {
+        let ranges: Vec<(i32, i32)> = presets()
+            .iter()
+            .map(|p| (p.operand1.min, p.operand1.max))
+            .collect();
+        assert_eq!(ranges, vec![(1, 9), (10, 99), (100, 999), (1000, 9999)]);
+    }
+
+    #[test]
+    fn test_operand_range() {
+        assert_eq!(OperandRange::new(20, 5), OperandRange { min: 5, max: 20 });
+        assert_eq!(
+            OperandRange::new(0, 20000),
+            OperandRange::new(1, MAX_OPERAND)
+        );
+        assert_eq!(OperandRange::digits(3).digit_count(), 3);
+        assert_eq!(OperandRange::digits(9), OperandRange::digits(MAX_DIGITS));
+        assert_eq!(OperandRange::new(1, 12).digit_count(), 2);
+    }
+
+    #[test]
+    fn test_bands_skip_easy_problems() {
+        let sum = |a, b| {
+            Complexity::of(&Expr::binary(
+                BinaryOp::Add,
+                Expr::number(a),
+                Expr::number(b),
+            ))
+        };
+        let presets = presets();
+        let (medium, hard) = (presets[1].complexity, presets[2].complexity);
+        assert!(!medium.contains(&sum(30, 40)));
+        assert!(medium.contains(&sum(47, 38)));
+        assert!(!hard.contains(&sum(500, 500)));
+        assert!(hard.contains(&sum(478, 365)));
+    }
+}
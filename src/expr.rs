@@ -0,0 +1,494 @@
+use crate::decimal::Decimal;
+use crate::game::Answer;
+use crate::rational::Rational;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    // DivRem is whole number division that answers with a quotient and a remainder
+    DivRem,
+}
+
+impl BinaryOp {
+    fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Add | BinaryOp::Sub => 1,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::DivRem => 2,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::DivRem => "÷",
+        }
+    }
+}
+
+// Expr is the tree of a problem. The text shown to the player and the expected answer are both
+// derived from it so they can never disagree.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    Number(i32),
+    Fraction { numerator: i32, denominator: i32 },
+    Decimal(Decimal),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Paren(Box<Expr>),
+    // Hidden is an operand the player has to find, it is shown as "?" but keeps its value
+    Hidden(Box<Expr>),
+}
+
+// Value is an intermediate result while evaluating. Anything involving a fraction stays a
+// fraction, so fraction problems always expect a fraction answer. Decimals stay decimals as
+// long as the result terminates.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Value {
+    Integer(i64),
+    Decimal(Decimal),
+    Fraction(Rational),
+    // a quotient and remainder pair is a final answer, it can't be used as an operand
+    QuotientRemainder(i64, i64),
+}
+
+impl Value {
+    fn as_rational(self) -> Option<Rational> {
+        match self {
+            Value::Integer(n) => Some(Rational::from_integer(n)),
+            Value::Decimal(d) => d.to_rational(),
+            Value::Fraction(r) => Some(r),
+            Value::QuotientRemainder(_, _) => None,
+        }
+    }
+
+    fn as_decimal(self) -> Option<Decimal> {
+        match self {
+            Value::Integer(n) => Some(Decimal::from(n)),
+            Value::Decimal(d) => Some(d),
+            Value::Fraction(_) | Value::QuotientRemainder(_, _) => None,
+        }
+    }
+}
+
+impl Expr {
+    pub fn number(n: i32) -> Self {
+        Expr::Number(n)
+    }
+
+    pub fn fraction(numerator: i32, denominator: i32) -> Self {
+        Expr::Fraction {
+            numerator,
+            denominator,
+        }
+    }
+
+    pub fn binary(op: BinaryOp, left: Expr, right: Expr) -> Self {
+        Expr::Binary(op, Box::new(left), Box::new(right))
+    }
+
+    // numbers lists the literal operands from left to right, a decimal by its mantissa which
+    // can be longer than an i32
+    pub fn numbers(&self) -> Vec<i64> {
+        match self {
+            Expr::Number(n) => vec![*n as i64],
+            Expr::Fraction {
+                numerator,
+                denominator,
+            } => vec![*numerator as i64, *denominator as i64],
+            Expr::Decimal(d) => vec![d.mantissa()],
+            Expr::Paren(inner) | Expr::Hidden(inner) => inner.numbers(),
+            Expr::Binary(_, left, right) => {
+                let mut numbers = left.numbers();
+                numbers.extend(right.numbers());
+                numbers
+            }
+        }
+    }
+
+    // operand_count counts the literal operands, a fraction is one operand
+    pub fn operand_count(&self) -> usize {
+        match self {
+            Expr::Number(_) | Expr::Fraction { .. } | Expr::Decimal(_) => 1,
+            Expr::Paren(inner) | Expr::Hidden(inner) => inner.operand_count(),
+            Expr::Binary(_, left, right) => left.operand_count() + right.operand_count(),
+        }
+    }
+
+    // hide_operand hides the literal operand at index, counted from the left, and returns the
+    // new tree with the hidden literal. None when there is no such operand.
+    pub fn hide_operand(&self, index: usize) -> Option<(Expr, Expr)> {
+        match self {
+            Expr::Number(_) | Expr::Fraction { .. } | Expr::Decimal(_) => {
+                (index == 0).then(|| (Expr::Hidden(Box::new(self.clone())), self.clone()))
+            }
+            Expr::Paren(inner) => inner
+                .hide_operand(index)
+                .map(|(expr, hidden)| (Expr::Paren(Box::new(expr)), hidden)),
+            // an operand is hidden at most once
+            Expr::Hidden(_) => None,
+            Expr::Binary(op, left, right) => {
+                let left_count = left.operand_count();
+                if index < left_count {
+                    let (expr, hidden) = left.hide_operand(index)?;
+                    Some((Expr::binary(*op, expr, (**right).clone()), hidden))
+                } else {
+                    let (expr, hidden) = right.hide_operand(index - left_count)?;
+                    Some((Expr::binary(*op, (**left).clone(), expr), hidden))
+                }
+            }
+        }
+    }
+
+    fn is_fraction(&self) -> bool {
+        match self {
+            Expr::Fraction { .. } => true,
+            Expr::Hidden(inner) => inner.is_fraction(),
+            _ => false,
+        }
+    }
+
+    // evaluate returns None when the expression divides by zero or leaves the i32 range
+    pub fn evaluate(&self) -> Option<Answer> {
+        match self.value()? {
+            Value::Integer(n) => i32::try_from(n).ok().map(Answer::Integer),
+            Value::Decimal(d) => Some(Answer::Decimal(d)),
+            Value::Fraction(r) => {
+                i32::try_from(r.numerator()).ok()?;
+                i32::try_from(r.denominator()).ok()?;
+                Some(Answer::Fraction(r))
+            }
+            Value::QuotientRemainder(q, r) => Some(Answer::QuotientRemainder {
+                quotient: i32::try_from(q).ok()?,
+                remainder: i32::try_from(r).ok()?,
+            }),
+        }
+    }
+
+    fn value(&self) -> Option<Value> {
+        match self {
+            Expr::Number(n) => Some(Value::Integer(*n as i64)),
+            Expr::Fraction {
+                numerator,
+                denominator,
+            } => Rational::new(*numerator as i64, *denominator as i64).map(Value::Fraction),
+            Expr::Decimal(d) => Some(Value::Decimal(*d)),
+            Expr::Binary(op, left, right) => apply(*op, left.value()?, right.value()?),
+            Expr::Paren(inner) | Expr::Hidden(inner) => inner.value(),
+        }
+    }
+}
+
+fn apply(op: BinaryOp, left: Value, right: Value) -> Option<Value> {
+    if let (Value::Integer(a), Value::Integer(b)) = (left, right) {
+        return match op {
+            BinaryOp::Add => a.checked_add(b).map(Value::Integer),
+            BinaryOp::Sub => a.checked_sub(b).map(Value::Integer),
+            BinaryOp::Mul => a.checked_mul(b).map(Value::Integer),
+            BinaryOp::Div => {
+                if b == 0 {
+                    None
+                } else if a % b == 0 {
+                    Some(Value::Integer(a / b))
+                } else {
+                    Rational::new(a, b).map(Value::Fraction)
+                }
+            }
+            // the remainder is never negative: -7 ÷ 2 is -4 r 1
+            BinaryOp::DivRem => {
+                (b > 0).then(|| Value::QuotientRemainder(a.div_euclid(b), a.rem_euclid(b)))
+            }
+        };
+    }
+
+    if let (Some(a), Some(b)) = (left.as_decimal(), right.as_decimal()) {
+        let result = match op {
+            BinaryOp::Add => a.checked_add(b),
+            BinaryOp::Sub => a.checked_sub(b),
+            BinaryOp::Mul => a.checked_mul(b),
+            BinaryOp::Div => a.checked_div(b),
+            BinaryOp::DivRem => None,
+        };
+        // a quotient that doesn't terminate falls through to an exact fraction
+        if let Some(d) = result {
+            return Some(Value::Decimal(d));
+        }
+    }
+
+    let (a, b) = (left.as_rational()?, right.as_rational()?);
+    let result = match op {
+        BinaryOp::Add => a.checked_add(b),
+        BinaryOp::Sub => a.checked_sub(b),
+        BinaryOp::Mul => a.checked_mul(b),
+        BinaryOp::Div => a.checked_div(b),
+        // remainders only exist for whole numbers
+        BinaryOp::DivRem => None,
+    };
+    result.map(Value::Fraction)
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_expr(f, self, true)
+    }
+}
+
+// write_expr prints expr, leftmost tells whether it starts the printed text or the text inside
+// a pair of parentheses. Only there a negative number can go without parentheses.
+fn write_expr(f: &mut fmt::Formatter<'_>, expr: &Expr, leftmost: bool) -> fmt::Result {
+    match expr {
+        Expr::Number(n) => write!(f, "{}", n),
+        Expr::Fraction {
+            numerator,
+            denominator,
+        } => write!(f, "{}/{}", numerator, denominator),
+        Expr::Decimal(d) => write!(f, "{}", d),
+        Expr::Binary(op, left, right) => {
+            write_operand(f, left, op.precedence(), false, leftmost)?;
+            // "1/2 / 3/4" would be ambiguous, fraction division uses the division sign
+            let fraction_operand = left.is_fraction() || right.is_fraction();
+            if *op == BinaryOp::Div && fraction_operand {
+                write!(f, " ÷ ")?;
+            } else {
+                write!(f, " {} ", op.symbol())?;
+            }
+            write_operand(f, right, op.precedence(), true, false)
+        }
+        Expr::Paren(inner) => {
+            write!(f, "(")?;
+            write_expr(f, inner, true)?;
+            write!(f, ")")
+        }
+        Expr::Hidden(_) => write!(f, "?"),
+    }
+}
+
+// write_operand adds the parentheses the tree needs so the printed text evaluates the same way
+// as the tree: lower precedence children, right children of equal precedence and negative
+// numbers that are not the leftmost token.
+fn write_operand(
+    f: &mut fmt::Formatter<'_>,
+    expr: &Expr,
+    parent: u8,
+    is_right: bool,
+    leftmost: bool,
+) -> fmt::Result {
+    let needs_paren = match expr {
+        Expr::Binary(op, _, _) => {
+            op.precedence() < parent || (is_right && op.precedence() == parent)
+        }
+        Expr::Number(n) => *n < 0 && !leftmost,
+        Expr::Fraction { numerator, .. } => *numerator < 0 && !leftmost,
+        Expr::Decimal(d) => *d < Decimal::from(0) && !leftmost,
+        Expr::Paren(_) | Expr::Hidden(_) => false,
+    };
+
+    if needs_paren {
+        write!(f, "(")?;
+        write_expr(f, expr, true)?;
+        write!(f, ")")
+    } else {
+        write_expr(f, expr, leftmost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_simple_binary() {
+        let expr = Expr::binary(BinaryOp::Add, Expr::number(2), Expr::number(3));
+        assert_eq!(expr.to_string(), "2 + 3");
+        assert_eq!(expr.evaluate(), Some(Answer::Integer(5)));
+    }
+
+    #[test]
+    fn test_display_adds_needed_parentheses() {
+        // (2 + 3) * 4
+        let expr = Expr::binary(
+            BinaryOp::Mul,
+            Expr::binary(BinaryOp::Add, Expr::number(2), Expr::number(3)),
+            Expr::number(4),
+        );
+        assert_eq!(expr.to_string(), "(2 + 3) * 4");
+        assert_eq!(expr.evaluate(), Some(Answer::Integer(20)));
+
+        // 10 - (4 - 1)
+        let expr = Expr::binary(
+            BinaryOp::Sub,
+            Expr::number(10),
+            Expr::binary(BinaryOp::Sub, Expr::number(4), Expr::number(1)),
+        );
+        assert_eq!(expr.to_string(), "10 - (4 - 1)");
+        assert_eq!(expr.evaluate(), Some(Answer::Integer(7)));
+
+        // 2 + 3 * 4 needs none
+        let expr = Expr::binary(
+            BinaryOp::Add,
+            Expr::number(2),
+            Expr::binary(BinaryOp::Mul, Expr::number(3), Expr::number(4)),
+        );
+        assert_eq!(expr.to_string(), "2 + 3 * 4");
+        assert_eq!(expr.evaluate(), Some(Answer::Integer(14)));
+    }
+
+    #[test]
+    fn test_hide_operand() {
+        // 12 + 9 * 3
+        let expr = Expr::binary(
+            BinaryOp::Add,
+            Expr::number(12),
+            Expr::binary(BinaryOp::Mul, Expr::number(9), Expr::number(3)),
+        );
+        assert_eq!(expr.operand_count(), 3);
+
+        let (hidden_expr, hidden) = expr.hide_operand(1).unwrap();
+        assert_eq!(hidden_expr.to_string(), "12 + ? * 3");
+        assert_eq!(hidden, Expr::number(9));
+        assert_eq!(hidden_expr.evaluate(), expr.evaluate());
+        assert_eq!(expr.hide_operand(3), None);
+        assert_eq!(hidden_expr.hide_operand(1), None);
+
+        let expr = Expr::binary(BinaryOp::Div, Expr::fraction(1, 2), Expr::fraction(3, 4));
+        let (hidden_expr, hidden) = expr.hide_operand(0).unwrap();
+        assert_eq!(hidden_expr.to_string(), "? ÷ 3/4");
+        assert_eq!(hidden, Expr::fraction(1, 2));
+    }
+
+    #[test]
+    fn test_negative_operands_are_parenthesized() {
+        let expr = Expr::binary(BinaryOp::Sub, Expr::number(12), Expr::number(-5));
+        assert_eq!(expr.to_string(), "12 - (-5)");
+        assert_eq!(expr.evaluate(), Some(Answer::Integer(17)));
+
+        let expr = Expr::binary(BinaryOp::Mul, Expr::number(-7), Expr::number(8));
+        assert_eq!(expr.to_string(), "-7 * 8");
+        assert_eq!(expr.evaluate(), Some(Answer::Integer(-56)));
+
+        // 5 - -8 / 4, the negative dividend is not the leftmost token
+        let expr = Expr::binary(
+            BinaryOp::Sub,
+            Expr::number(5),
+            Expr::binary(BinaryOp::Div, Expr::number(-8), Expr::number(4)),
+        );
+        assert_eq!(expr.to_string(), "5 - (-8) / 4");
+        assert_eq!(expr.evaluate(), Some(Answer::Integer(7)));
+
+        // (-2 + 3) * 4, a negative number right after an opening parenthesis is leftmost
+        let expr = Expr::binary(
+            BinaryOp::Mul,
+            Expr::binary(BinaryOp::Add, Expr::number(-2), Expr::number(3)),
+            Expr::number(4),
+        );
+        assert_eq!(expr.to_string(), "(-2 + 3) * 4");
+    }
+
+    #[test]
+    fn test_fraction_evaluation() {
+        let expr = Expr::binary(BinaryOp::Add, Expr::fraction(1, 2), Expr::fraction(1, 3));
+        assert_eq!(expr.to_string(), "1/2 + 1/3");
+        assert_eq!(
+            expr.evaluate(),
+            Some(Answer::Fraction(Rational::new(5, 6).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_fraction_division_sign() {
+        let expr = Expr::binary(BinaryOp::Div, Expr::fraction(1, 2), Expr::fraction(3, 4));
+        assert_eq!(expr.to_string(), "1/2 ÷ 3/4");
+        assert_eq!(
+            expr.evaluate(),
+            Some(Answer::Fraction(Rational::new(2, 3).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_decimal_evaluation() {
+        let d = |s: &str| s.parse::<Decimal>().unwrap();
+        let expr = Expr::binary(
+            BinaryOp::Add,
+            Expr::Decimal(d("0.1")),
+            Expr::Decimal(d("0.2")),
+        );
+        assert_eq!(expr.to_string(), "0.1 + 0.2");
+        assert_eq!(expr.evaluate(), Some(Answer::Decimal(d("0.3"))));
+
+        let expr = Expr::binary(BinaryOp::Mul, Expr::Decimal(d("1.5")), Expr::number(3));
+        assert_eq!(expr.evaluate(), Some(Answer::Decimal(d("4.5"))));
+
+        let expr = Expr::binary(
+            BinaryOp::Sub,
+            Expr::Decimal(d("1")),
+            Expr::Decimal(d("-0.5")),
+        );
+        assert_eq!(expr.to_string(), "1 - (-0.5)");
+
+        // 1 / 0.3 doesn't terminate, it stays exact as a fraction
+        let expr = Expr::binary(BinaryOp::Div, Expr::number(1), Expr::Decimal(d("0.3")));
+        assert_eq!(
+            expr.evaluate(),
+            Some(Answer::Fraction(Rational::new(10, 3).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_division_with_remainder() {
+        let expr = Expr::binary(BinaryOp::DivRem, Expr::number(17), Expr::number(5));
+        assert_eq!(expr.to_string(), "17 ÷ 5");
+        assert_eq!(
+            expr.evaluate(),
+            Some(Answer::QuotientRemainder {
+                quotient: 3,
+                remainder: 2
+            })
+        );
+
+        let expr = Expr::binary(BinaryOp::DivRem, Expr::number(-7), Expr::number(2));
+        assert_eq!(
+            expr.evaluate(),
+            Some(Answer::QuotientRemainder {
+                quotient: -4,
+                remainder: 1
+            })
+        );
+
+        let expr = Expr::binary(BinaryOp::DivRem, Expr::fraction(1, 2), Expr::number(2));
+        assert_eq!(expr.evaluate(), None);
+
+        // a remainder answer can't be added to
+        let expr = Expr::binary(
+            BinaryOp::Add,
+            Expr::binary(BinaryOp::DivRem, Expr::number(7), Expr::number(2)),
+            Expr::number(1),
+        );
+        assert_eq!(expr.evaluate(), None);
+    }
+
+    #[test]
+    fn test_integer_division() {
+        let exact = Expr::binary(BinaryOp::Div, Expr::number(12), Expr::number(4));
+        assert_eq!(exact.evaluate(), Some(Answer::Integer(3)));
+
+        let inexact = Expr::binary(BinaryOp::Div, Expr::number(7), Expr::number(2));
+        assert_eq!(
+            inexact.evaluate(),
+            Some(Answer::Fraction(Rational::new(7, 2).unwrap()))
+        );
+
+        let by_zero = Expr::binary(BinaryOp::Div, Expr::number(7), Expr::number(0));
+        assert_eq!(by_zero.evaluate(), None);
+    }
+
+    #[test]
+    fn test_overflow_is_none() {
+        let expr = Expr::binary(BinaryOp::Mul, Expr::number(i32::MAX), Expr::number(2));
+        assert_eq!(expr.evaluate(), None);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::difficulty::OperandRange;
+use crate::game::GameType;
+use crate::rng::RandomSource;
+use serde::{Deserialize, Serialize};
+
+// Focus narrows a round down to a few facts to drill. It applies to whole number additions,
+// subtractions, multiplications and divisions, other game types ignore it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Focus {
+    // one operand is always one of the tables, the other one comes from the difficulty
+    Tables(Vec<i32>),
+    // both factors are the same number, or both addends for additions
+    Squares,
+    // the four related facts of two numbers: 6 * 7, 7 * 6, 42 / 6 and 42 / 7 or
+    // 6 + 7, 7 + 6, 13 - 6 and 13 - 7
+    FactFamily(i32, i32),
+}
+
+impl Focus {
+    // operands picks the game type and the operands of the next problem in the order the
+    // generators use them: a division takes the quotient and the divisor, a subtraction takes
+    // the minuend and the subtrahend. None when the focus doesn't apply to the game type.
+    pub fn operands(
+        &self,
+        rng: &mut dyn RandomSource,
+        game_type: GameType,
+        range: OperandRange,
+    ) -> Option<(GameType, i32, i32)> {
+        if !matches!(
+            game_type,
+            GameType::Addition
+                | GameType::Subtraction
+                | GameType::Multiplication
+                | GameType::Division
+        ) {
+            return None;
+        }
+
+        match self {
+            Focus::Tables(tables) => {
+                if tables.is_empty() {
+                    return None;
+                }
+                let table = tables[rng.gen_range(0, tables.len() as i32 - 1) as usize];
+                let other = rng.gen_range(range.min, range.max);
+                let operands = match game_type {
+                    GameType::Subtraction => (other + table, table),
+                    GameType::Division => (other, table),
+                    // the table number shows up on either side
+                    _ if rng.gen_range(0, 1) == 0 => (table, other),
+                    _ => (other, table),
+                };
+                Some((game_type, operands.0, operands.1))
+            }
+            Focus::Squares => {
+                let n = rng.gen_range(range.min, range.max);
+                match game_type {
+                    GameType::Subtraction => Some((game_type, n + n, n)),
+                    _ => Some((game_type, n, n)),
+                }
+            }
+            Focus::FactFamily(x, y) => {
+                let (x, y) = (*x, *y);
+                let (forward, inverse) = match game_type {
+                    GameType::Addition | GameType::Subtraction => {
+                        (GameType::Addition, GameType::Subtraction)
+                    }
+                    _ => (GameType::Multiplication, GameType::Division),
+                };
+                let facts = [
+                    (forward, x, y),
+                    (forward, y, x),
+                    // x + y - x and x * y / x
+                    match inverse {
+                        GameType::Subtraction => (inverse, x + y, x),
+                        _ => (inverse, y, x),
+                    },
+                    match inverse {
+                        GameType::Subtraction => (inverse, x + y, y),
+                        _ => (inverse, x, y),
+                    },
+                ];
+                Some(facts[rng.gen_range(0, facts.len() as i32 - 1) as usize])
+            }
+        }
+    }
+
+    // fact_count is about how many facts the focus has within the range, a round with more
+    // problems than that has to repeat some
+    pub fn fact_count(&self, range: OperandRange) -> usize {
+        let numbers = (range.max - range.min + 1) as usize;
+        match self {
+            Focus::Tables(tables) => tables.len() * numbers,
+            Focus::Squares => numbers,
+            Focus::FactFamily(_, _) => 4,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SeededRng;
+
+    #[test]
+    fn test_tables_pin_one_operand() {
+        let mut rng = SeededRng::new(2);
+        let focus = Focus::Tables(vec![7, 8]);
+        let range = OperandRange::digits(1);
+        for _ in 0..100 {
+            let (_, a, b) = focus
+                .operands(&mut rng, GameType::Multiplication, range)
+                .unwrap();
+            assert!([7, 8].contains(&a) || [7, 8].contains(&b), "{} * {}", a, b);
+
+            let (_, _, divisor) = focus.operands(&mut rng, GameType::Division, range).unwrap();
+            assert!([7, 8].contains(&divisor));
+        }
+        assert_eq!(
+            Focus::Tables(Vec::new()).operands(&mut rng, GameType::Multiplication, range),
+            None
+        );
+        assert_eq!(
+            focus.operands(&mut rng, GameType::FractionAddition, range),
+            None
+        );
+    }
+
+    #[test]
+    fn test_squares() {
+        let mut rng = SeededRng::new(3);
+        for _ in 0..50 {
+            let (_, a, b) = Focus::Squares
+                .operands(&mut rng, GameType::Multiplication, OperandRange::digits(1))
+                .unwrap();
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn test_fact_family() {
+        let mut rng = SeededRng::new(4);
+        let family = Focus::FactFamily(6, 7);
+        let mut facts = std::collections::HashSet::new();
+        for _ in 0..100 {
+            facts.insert(
+                family
+                    .operands(&mut rng, GameType::Division, OperandRange::digits(1))
+                    .unwrap(),
+            );
+        }
+        let expected = [
+            (GameType::Multiplication, 6, 7),
+            (GameType::Multiplication, 7, 6),
+            (GameType::Division, 7, 6),
+            (GameType::Division, 6, 7),
+        ];
+        assert_eq!(facts, expected.into_iter().collect());
+
+        let (game_type, a, b) = family
+            .operands(&mut rng, GameType::Subtraction, OperandRange::digits(1))
+            .unwrap();
+        assert!(matches!(
+            game_type,
+            GameType::Addition | GameType::Subtraction
+        ));
+        assert!(a == 13 || a + b == 13, "{:?} {} {}", game_type, a, b);
+        assert_eq!(family.fact_count(OperandRange::digits(2)), 4);
+        assert_eq!(
+            Focus::Tables(vec![7, 8]).fact_count(OperandRange::digits(1)),
+            18
+        );
+    }
+}
@@ -1,86 +1,318 @@
-#[cfg(not(test))]
-use rand::Rng;
+use crate::adaptive::Staircase;
+use crate::chain;
+use crate::complexity::Complexity;
+use crate::decimal::Decimal;
+use crate::deck::{self, Deck};
+use crate::difficulty::{self, DifficultyProfile, OperandRange};
+use crate::expr::{BinaryOp, Expr};
+use crate::focus::Focus;
+use crate::placement::{Placement, PlacementResult};
+use crate::quality::{self, Regrouping};
+use crate::rating::Ratings;
+use crate::rational::Rational;
+use crate::review::{Fact, Schedule};
+use crate::rng::{RandomSource, SeededRng};
+use crate::scoring::{ScoreBreakdown, ScoringPolicy};
+use crate::stats::RoundStats;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum GameDifficulty {
     Basic,
     Medium,
     Hard,
     Mastery,
+    // Custom is a user-defined difficulty profile, identified by its id
+    Custom(u32),
 }
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum GameType {
     Addition,
     Subtraction,
     Multiplication,
     Division,
+    DivisionWithRemainder,
     FractionAddition,
     FractionSubtraction,
+    FractionMultiplication,
+    FractionDivision,
+    DecimalAddition,
+    DecimalSubtraction,
+    DecimalMultiplication,
+    DecimalDivision,
+    Chain,
+    // Mixed picks one of the weighted operations for every problem
+    Mixed,
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
+// Answers are ordered by value within a variant, then Integer < Fraction < Decimal
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub enum Answer {
     Integer(i32),
-    Fraction { numerator: i32, denominator: i32 },
+    Fraction(Rational),
+    Decimal(Decimal),
+    QuotientRemainder { quotient: i32, remainder: i32 },
 }
-// check this
+
 impl fmt::Display for Answer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Answer::Integer(n) => write!(f, "{}", n),
-            Answer::Fraction {
-                numerator,
-                denominator,
-            } => write!(f, "{}/{}", numerator, denominator),
+            Answer::Fraction(r) => write!(f, "{}", r),
+            Answer::Decimal(d) => write!(f, "{}", d),
+            Answer::QuotientRemainder {
+                quotient,
+                remainder,
+            } => write!(f, "{} r {}", quotient, remainder),
         }
     }
 }
 
 impl Answer {
+    // check accepts any equivalent fraction, 2/4 is the same answer as 1/2
     pub fn check(&self, user_answer: &Answer) -> bool {
         match (self, user_answer) {
             (Answer::Integer(a), Answer::Integer(b)) => a == b,
+            (Answer::Fraction(a), Answer::Fraction(b)) => a == b,
+            // 0.5 and 0.50 are the same decimal
+            (Answer::Decimal(a), Answer::Decimal(b)) => a == b,
             (
-                Answer::Fraction {
-                    numerator: n1,
-                    denominator: d1,
+                Answer::QuotientRemainder {
+                    quotient: q1,
+                    remainder: r1,
+                },
+                Answer::QuotientRemainder {
+                    quotient: q2,
+                    remainder: r2,
                 },
-                Answer::Fraction {
-                    numerator: n2,
-                    denominator: d2,
+            ) => q1 == q2 && r1 == r2,
+            // a bare quotient has no remainder
+            (
+                Answer::QuotientRemainder {
+                    quotient,
+                    remainder,
                 },
-            ) => n1 * d2 == n2 * d1,
+                Answer::Integer(b),
+            ) => quotient == b && *remainder == 0,
             _ => false,
         }
     }
+
+    // parse_integer accepts a leading minus typed as "-" or as one of the Unicode minus signs
+    pub fn parse_integer(input: &str) -> Option<Answer> {
+        normalize_minus(input)
+            .trim()
+            .parse()
+            .ok()
+            .map(Answer::Integer)
+    }
+
+    // parse_decimal accepts "0.5", ".5" and "0.50" as the same answer
+    pub fn parse_decimal(input: &str) -> Option<Answer> {
+        normalize_minus(input).parse().ok().map(Answer::Decimal)
+    }
+
+    // parse_quotient_remainder reads "17 r 3", "17r3" or "17 R 3". A bare "17" stays a whole
+    // number, it means no remainder but may still be on the way to "17 r 3".
+    pub fn parse_quotient_remainder(input: &str) -> Option<Answer> {
+        let input = normalize_minus(input).trim().to_lowercase();
+        let Some((quotient, remainder)) = input.split_once('r') else {
+            return Answer::parse_integer(&input);
+        };
+        let (quotient, remainder) = (quotient.trim(), remainder.trim());
+        Some(Answer::QuotientRemainder {
+            quotient: quotient.parse().ok()?,
+            remainder: remainder.parse().ok()?,
+        })
+    }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Fraction {
-    pub numerator: i32,
-    pub denominator: i32,
+// normalize_minus replaces the minus signs keyboards and copy-paste produce with "-"
+pub fn normalize_minus(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| match c {
+            '\u{2212}' | '\u{2013}' | '\u{FE63}' | '\u{FF0D}' => '-',
+            c => c,
+        })
+        .collect()
 }
 
-impl Fraction {
-    fn new(numerator: i32, denominator: i32) -> Self {
-        Self {
+// count_digits counts the digits of a typed or shown answer, signs, decimal points and the r
+// of a remainder don't count
+pub fn count_digits(input: &str) -> usize {
+    input.chars().filter(char::is_ascii_digit).count()
+}
+
+// SubmitMode decides when a typed answer is checked
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubmitMode {
+    // checked while it is typed and taken as soon as it is right. A wrong answer only counts
+    // once it has as many digits as the expected one.
+    ExactMatch,
+    // checked when Enter is pressed
+    Enter,
+    // checked once it has as many digits as the expected answer
+    DigitCount,
+}
+
+// FractionPolicy decides which written forms of a correct fraction value are accepted in a round
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FractionPolicy {
+    AnyEquivalent,
+    LowestTerms,
+    // improper results must be written as a mixed number, always in lowest terms
+    MixedNumber,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Verdict {
+    Correct,
+    Incorrect,
+    // the value is right but the answer policy wants another form
+    NotLowestTerms,
+    NotMixedNumber,
+}
+
+impl Verdict {
+    pub fn is_correct(&self) -> bool {
+        *self == Verdict::Correct
+    }
+
+    pub fn feedback(&self) -> Option<&'static str> {
+        match self {
+            Verdict::Correct | Verdict::Incorrect => None,
+            Verdict::NotLowestTerms => {
+                Some("That's the right value, now simplify it to lowest terms")
+            }
+            Verdict::NotMixedNumber => {
+                Some("That's the right value, now write it as a mixed number")
+            }
+        }
+    }
+}
+
+// WrittenFraction is a fraction answer the way the player typed it. Unlike Rational it is not
+// normalized, so the answer policy can tell 2/4 from 1/2. A negative mixed number carries its
+// sign on the whole part: -1 1/2 is -(1 + 1/2).
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrittenFraction {
+    pub whole: i64,
+    pub numerator: i64,
+    pub denominator: i64,
+    // whole_only tells that the fraction fields are empty, a mixed number may still be typed
+    pub whole_only: bool,
+}
+
+impl WrittenFraction {
+    // parse reads the whole, numerator and denominator fields, an empty whole part is 0 and a
+    // whole number alone is accepted
+    pub fn parse(whole: &str, numerator: &str, denominator: &str) -> Option<Self> {
+        let (whole, numerator, denominator) = (
+            normalize_minus(whole),
+            normalize_minus(numerator),
+            normalize_minus(denominator),
+        );
+        let (whole, numerator, denominator) = (whole.trim(), numerator.trim(), denominator.trim());
+        let whole_part = if whole.is_empty() {
+            0
+        } else {
+            whole.parse::<i64>().ok()?
+        };
+
+        let whole_only = numerator.is_empty() && denominator.is_empty();
+        let (numerator, denominator) = if whole_only {
+            if whole.is_empty() {
+                return None;
+            }
+            (0, 1)
+        } else {
+            (
+                numerator.parse::<i64>().ok()?,
+                denominator.parse::<i64>().ok()?,
+            )
+        };
+
+        if denominator == 0 || (whole_part != 0 && numerator < 0) {
+            return None;
+        }
+        Some(Self {
+            whole: whole_part,
             numerator,
             denominator,
+            whole_only,
+        })
+    }
+
+    pub fn value(&self) -> Option<Rational> {
+        let fraction = Rational::new(self.numerator, self.denominator)?;
+        let whole = Rational::from_integer(self.whole);
+        if self.whole < 0 {
+            whole.checked_sub(fraction)
+        } else {
+            whole.checked_add(fraction)
         }
     }
 
-    fn to_string(&self) -> String {
-        format!("{}/{}", self.numerator, self.denominator)
+    fn is_lowest_terms(&self) -> bool {
+        Rational::new(self.numerator, self.denominator)
+            .is_some_and(|r| r.denominator() == self.denominator.abs())
+    }
+
+    fn is_mixed_number(&self) -> bool {
+        self.whole != 0 && self.numerator.abs() < self.denominator.abs()
+    }
+
+    pub fn verdict(&self, expected: &Answer, policy: FractionPolicy) -> Verdict {
+        let value = match (self.value(), expected) {
+            (Some(value), Answer::Fraction(expected)) if value == *expected => value,
+            _ => return Verdict::Incorrect,
+        };
+
+        let improper = !value.is_integer() && value.numerator().abs() > value.denominator();
+        match policy {
+            FractionPolicy::AnyEquivalent => Verdict::Correct,
+            FractionPolicy::MixedNumber if improper && !self.is_mixed_number() => {
+                Verdict::NotMixedNumber
+            }
+            FractionPolicy::LowestTerms | FractionPolicy::MixedNumber => {
+                if self.is_lowest_terms() {
+                    Verdict::Correct
+                } else {
+                    Verdict::NotLowestTerms
+                }
+            }
+        }
     }
 }
 
+// more places would overflow the operand ranges at Mastery
+pub const MAX_DECIMAL_PLACES: u32 = 3;
+// sprint rounds last one of these many seconds
+pub const SPRINT_LENGTHS: [u32; 3] = [60, 120, 300];
+// a narrow profile can run out of acceptable problems, then the last candidate is used
+const MAX_GENERATION_ATTEMPTS: usize = 100;
+
+// Attempt is one answer given to a problem, right or wrong
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    pub value: Answer,
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub correct: bool,
+}
+
 #[derive(PartialEq)]
 pub enum GameState {
     NotStarted,
     Playing,
     RoundComplete,
+    // a sprint round ran out of time
+    TimeUp,
+    // every life of the round was lost to mistakes
+    OutOfLives,
 }
 
 pub struct Game {
@@ -91,12 +323,68 @@ pub struct Game {
     pub problems_per_round: i32,
     pub current_round_completed_problems: i32,
     pub game_state: GameState,
-    #[cfg(test)]
-    rng: fn(i32, i32) -> (i32, i32),
+    // sprint_seconds turns rounds into sprints: as many problems as possible before the time
+    // is up instead of a fixed number of problems
+    pub sprint_seconds: Option<u32>,
+    pub round_started_at: Option<chrono::DateTime<chrono::Utc>>,
+    // scoring decides the points of every answer and the cost of every mistake
+    pub scoring: ScoringPolicy,
+    // round_score is how the points of the round came together
+    pub round_score: ScoreBreakdown,
+    // streak counts the right answers in a row, a mistake starts it over
+    streak: u32,
+    // lives ends the round after that many wrong answers
+    pub lives: Option<u32>,
+    pub lives_left: u32,
+    pub chain_operands: usize,
+    pub mixed_operations: Vec<(GameType, u32)>,
+    pub fraction_policy: FractionPolicy,
+    pub submit_mode: SubmitMode,
+    // signed_numbers mixes negative operands into the whole number game types
+    pub signed_numbers: bool,
+    // missing_operands asks for an operand instead of the result now and then, "? * 6 = 42"
+    pub missing_operands: bool,
+    // focus drills a few facts instead of the whole difficulty range
+    pub focus: Option<Focus>,
+    // deck_mode deals every fact of the level once before any comes up again
+    pub deck_mode: bool,
+    pub decks: HashMap<(GameType, GameDifficulty), Deck>,
+    // schedule remembers how well every fact is known and when it is due for review
+    pub schedule: Schedule,
+    // review asks the due facts of the schedule before anything else
+    pub review: bool,
+    // adaptive moves the level up and down with the answers instead of a fixed difficulty
+    pub adaptive: bool,
+    // skills are the adaptive levels reached per game type, the estimated skill of the player
+    pub skills: HashMap<GameType, Staircase>,
+    // ratings rate the player per game type and difficulty and every problem against each other
+    pub ratings: Ratings,
+    // placement is the running placement test, it picks the level of every problem
+    pub placement: Option<Placement>,
+    // placements are the levels the last placement test found per game type
+    pub placements: HashMap<GameType, PlacementResult>,
+    pub decimal_places: HashMap<GameDifficulty, u32>,
+    // custom_profiles are the user-defined difficulties, the built-in ones are presets
+    pub custom_profiles: Vec<DifficultyProfile>,
+    // next_profile_id only goes up, the id of a removed profile is never given out again so
+    // nothing saved for it comes back with a new profile
+    pub next_profile_id: u32,
+    pub regrouping: Regrouping,
+    // round_problems holds the text of every problem of the round so none is asked twice
+    round_problems: HashSet<String>,
+    // round_history keeps every solved problem of the round with its times
+    pub round_history: Vec<Problem>,
+    // correct answers per level, mixed rounds count as their own level
+    level_scores: HashMap<(GameType, GameDifficulty), u32>,
+    // focus drills are levels of their own
+    focus_scores: HashMap<(GameType, Focus), u32>,
+    seed: u64,
+    rng: Box<dyn RandomSource>,
 }
 
 impl Default for Game {
     fn default() -> Self {
+        let rng = SeededRng::from_entropy();
         Self {
             score: 0,
             current_type: GameType::Addition,
@@ -105,8 +393,59 @@ impl Default for Game {
             problems_per_round: 10,
             current_round_completed_problems: 0,
             game_state: GameState::NotStarted,
-            #[cfg(test)]
-            rng: |min, max| (min, max),
+            sprint_seconds: None,
+            round_started_at: None,
+            scoring: ScoringPolicy::default(),
+            round_score: ScoreBreakdown::default(),
+            streak: 0,
+            lives: None,
+            lives_left: 0,
+            chain_operands: 4,
+            mixed_operations: vec![
+                (GameType::Addition, 1),
+                (GameType::Subtraction, 1),
+                (GameType::Multiplication, 1),
+                (GameType::Division, 1),
+                (GameType::DivisionWithRemainder, 0),
+                (GameType::FractionAddition, 0),
+                (GameType::FractionSubtraction, 0),
+                (GameType::FractionMultiplication, 0),
+                (GameType::FractionDivision, 0),
+                (GameType::DecimalAddition, 0),
+                (GameType::DecimalSubtraction, 0),
+                (GameType::DecimalMultiplication, 0),
+                (GameType::DecimalDivision, 0),
+                (GameType::Chain, 0),
+            ],
+            fraction_policy: FractionPolicy::AnyEquivalent,
+            submit_mode: SubmitMode::ExactMatch,
+            signed_numbers: false,
+            missing_operands: false,
+            focus: None,
+            deck_mode: false,
+            decks: HashMap::new(),
+            schedule: Schedule::default(),
+            review: false,
+            adaptive: false,
+            skills: HashMap::new(),
+            ratings: Ratings::default(),
+            placement: None,
+            placements: HashMap::new(),
+            decimal_places: HashMap::from([
+                (GameDifficulty::Basic, 1),
+                (GameDifficulty::Medium, 1),
+                (GameDifficulty::Hard, 2),
+                (GameDifficulty::Mastery, 3),
+            ]),
+            custom_profiles: Vec::new(),
+            next_profile_id: 0,
+            regrouping: Regrouping::Any,
+            round_problems: HashSet::new(),
+            round_history: Vec::new(),
+            level_scores: HashMap::new(),
+            focus_scores: HashMap::new(),
+            seed: rng.seed(),
+            rng: Box::new(rng),
         }
     }
 }
@@ -117,118 +456,552 @@ impl Game {
         Default::default()
     }
 
+    // with_seed creates a game whose problems are fully determined by the seed, so a round can
+    // be replayed for bug reports or shared as a challenge.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Box::new(SeededRng::new(seed)),
+            ..Default::default()
+        }
+    }
+
     #[cfg(test)]
     pub fn with_mock_rng(rng: fn(i32, i32) -> (i32, i32)) -> Self {
-        Self { 
-            score: 0,
-            current_type: GameType::Addition,
-            current_difficulty: GameDifficulty::Basic,
-            current_problem: None,
-            problems_per_round: 10,
-            current_round_completed_problems: 0,
-            game_state: GameState::NotStarted,
-            rng 
+        Self {
+            rng: Box::new(crate::rng::PairRng::new(rng)),
+            ..Default::default()
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // reseed restarts the random sequence, the next generated problem is the first one of the seed
+    // and of a new round
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = Box::new(SeededRng::new(seed));
+        self.round_problems.clear();
+        self.round_history.clear();
+        self.round_score = ScoreBreakdown::default();
+        self.streak = 0;
+    }
+
+    // start_round starts a round from the seed, the clock of a sprint starts with it. Points
+    // of a placement test before it don't count.
+    pub fn start_round(&mut self, seed: u64) {
+        self.reseed(seed);
+        self.score = 0;
+        self.current_round_completed_problems = 0;
+        self.generate_problem();
+        self.game_state = GameState::Playing;
+        self.round_started_at = Some(chrono::Utc::now());
+        self.lives_left = self.lives.unwrap_or(0);
+    }
+
+    // time_left is what is left of a sprint round, never below zero
+    pub fn time_left(&self, now: chrono::DateTime<chrono::Utc>) -> Option<chrono::Duration> {
+        let seconds = self.sprint_seconds.filter(|_| self.placement.is_none())?;
+        let elapsed = now - self.round_started_at?;
+        Some((chrono::Duration::seconds(seconds as i64) - elapsed).max(chrono::Duration::zero()))
+    }
+
+    // check_time ends a sprint round once its time is up
+    pub fn check_time(&mut self, now: chrono::DateTime<chrono::Utc>) {
+        if self.game_state == GameState::Playing
+            && self.time_left(now) == Some(chrono::Duration::zero())
+        {
+            self.game_state = GameState::TimeUp;
         }
     }
 
-    pub fn generate_problem(
-        &mut self
-    ) -> Problem {
-        let (min, max) = self.generate_range(&self.current_difficulty);
+    // generate_problem is None when no problem fits the settings, like borrows in every
+    // subtraction of single digits
+    pub fn generate_problem(&mut self) -> Option<Problem> {
+        if let Some((game_type, difficulty)) = self.placement.as_ref().and_then(Placement::next) {
+            self.current_type = game_type;
+            self.current_difficulty = difficulty;
+        }
+        let problem = self
+            .review_problem()
+            .or_else(|| self.deal_problem())
+            .or_else(|| self.find_problem());
+        if let Some(problem) = &problem {
+            self.round_problems.insert(problem.problem.clone());
+        }
+
+        // se the current problem to the generated problem
+        self.current_problem = problem.clone();
 
-        #[cfg(test)]
-        let (a, b) = (self.rng)(min, max);
+        problem
+    }
 
-        #[cfg(not(test))]
-        let (a, b) = {
-            let mut rng = rand::thread_rng();
-            (rng.gen_range(min..=max), rng.gen_range(min..=max))
+    fn build_candidate(&mut self) -> Problem {
+        let game_type = match self.current_type {
+            GameType::Mixed => self.pick_mixed_operation(),
+            game_type => game_type,
+        };
+        let range = self.active_profile().operand1;
+        let focused = self
+            .focus
+            .as_ref()
+            .and_then(|focus| focus.operands(self.rng.as_mut(), game_type, range));
+        let problem = match focused {
+            Some((game_type, a, b)) => Problem {
+                focused: true,
+                ..self.build_problem(game_type, Some((a, b)))
+            },
+            None => self.build_problem(game_type, None),
         };
+        self.pick_unknown(problem)
+    }
 
-        // math problems types to generate custom problems
-        let problem = match &self.current_type {
-            GameType::Addition => {
-                Problem::new(format!("{} + {}", a, b), Answer::Integer(a + b), a, b)
+    // pick_unknown hides an operand in missing operand mode, every operand and the result are
+    // equally likely to be the unknown
+    fn pick_unknown(&mut self, problem: Problem) -> Problem {
+        if !self.missing_operands {
+            return problem;
+        }
+        let operands = problem.expr.operand_count();
+        let unknown = self.rng.gen_range(0, operands as i32) as usize;
+        problem.hide_operand(unknown)
+    }
+
+    // review_problem asks the most overdue fact in review mode, without due facts the round
+    // goes on with the usual problems
+    fn review_problem(&mut self) -> Option<Problem> {
+        if !self.review {
+            return None;
+        }
+        let fact = self.schedule.next_due(chrono::Utc::now())?;
+        Some(self.pick_unknown(fact.problem()))
+    }
+
+    // due_facts is how many facts are waiting for a review
+    pub fn due_facts(&self) -> usize {
+        self.schedule.due_count(chrono::Utc::now())
+    }
+
+    // deal_problem takes the next fact from the deck of the level. Dealt problems skip the
+    // quality filters, the point of a deck is that every fact comes up.
+    fn deal_problem(&mut self) -> Option<Problem> {
+        if !self.deals_from_deck() {
+            return None;
+        }
+        let profile = self.profile(self.current_difficulty);
+        let facts = deck::facts(self.current_type, profile.operand1, profile.operand2)?;
+
+        let key = (self.current_type, self.current_difficulty);
+        if !self.decks.contains_key(&key) {
+            let seed = self.rng.gen_range(0, i32::MAX) as u64;
+            self.decks.insert(key, Deck::new(seed));
+        }
+        let operands = self.decks.get_mut(&key)?.deal(&facts)?;
+        let problem = Problem {
+            dealt: true,
+            ..self.build_problem(self.current_type, Some(operands))
+        };
+        Some(self.pick_unknown(problem))
+    }
+
+    // deals_from_deck tells whether problems come from the deck. The facts of an adaptive round
+    // change with the level, there is no deck to go through, and a focus drill or a placement
+    // test picks its own problems.
+    fn deals_from_deck(&self) -> bool {
+        self.deck_mode && self.focus.is_none() && !self.adaptive && self.placement.is_none()
+    }
+
+    // deck_progress is how many facts of the level's deck were answered out of how many
+    pub fn deck_progress(&self) -> Option<(usize, usize)> {
+        if !self.deals_from_deck() {
+            return None;
+        }
+        let profile = self.profile(self.current_difficulty);
+        let facts = deck::facts(self.current_type, profile.operand1, profile.operand2)?;
+        let dealt = self
+            .decks
+            .get(&(self.current_type, self.current_difficulty))
+            .map_or(0, |deck| deck.position());
+        Some((dealt, facts.len()))
+    }
+
+    // find_problem prefers a new problem that fits every constraint, then a repeat and then a
+    // trivial one that still fit. A problem that breaks a constraint is never asked, when none
+    // comes up the settings can't be met at this level.
+    fn find_problem(&mut self) -> Option<Problem> {
+        let (mut repeat, mut trivial) = (None, None);
+        for _ in 0..MAX_GENERATION_ATTEMPTS {
+            let problem = self.build_candidate();
+            if !self.fits_constraints(&problem) {
+                continue;
             }
-            GameType::Subtraction => {
-                Problem::new(format!("{} - {}", a, b), Answer::Integer(a - b), a, b)
+            if quality::is_trivial(&problem.expr, &problem.answer) {
+                trivial.get_or_insert(problem);
+            } else if self.round_problems.contains(&problem.problem)
+                && !self.repeats_allowed(&problem)
+            {
+                repeat.get_or_insert(problem);
+            } else {
+                return Some(problem);
             }
-            GameType::Multiplication => {
-                Problem::new(format!("{} * {}", a, b), Answer::Integer(a * b), a, b)
+        }
+        repeat.or(trivial)
+    }
+
+    // repeats_allowed lets a focus with fewer facts than the round repeat them, searching for
+    // a new one would only use up every attempt
+    fn repeats_allowed(&self, problem: &Problem) -> bool {
+        let range = self.active_profile().operand1;
+        problem.focused
+            && self.focus.as_ref().is_some_and(|focus| {
+                focus.fact_count(range) < self.problems_per_round.max(0) as usize
+            })
+    }
+
+    // fits_constraints checks the complexity band of the difficulty and the carry/borrow
+    // constraint of additions and subtractions. A focus picks its facts itself, so the band
+    // doesn't apply to the problems it picked, and the placement test asks plain problems of
+    // every level.
+    fn fits_constraints(&self, problem: &Problem) -> bool {
+        let profile = self.active_profile();
+        if !problem.focused && !profile.complexity.contains(&problem.complexity) {
+            return false;
+        }
+        match (problem.game_type, &problem.expr) {
+            (GameType::Addition | GameType::Subtraction, Expr::Binary(op, _, _))
+                if self.placement.is_none() =>
+            {
+                self.regrouping
+                    .allows(*op, problem.operand1, problem.operand2)
             }
+            _ => true,
+        }
+    }
+
+    // pick_mixed_operation falls back to addition when every operation has a zero weight
+    fn pick_mixed_operation(&mut self) -> GameType {
+        let weights: Vec<u32> = self
+            .mixed_operations
+            .iter()
+            .map(|(game_type, weight)| match game_type {
+                GameType::Mixed => 0,
+                _ => *weight,
+            })
+            .collect();
+        match self.rng.choose_weighted(&weights) {
+            Some(i) => self.mixed_operations[i].0,
+            None => GameType::Addition,
+        }
+    }
+
+    // build_problem draws the operands from the difficulty unless they are given
+    fn build_problem(&mut self, game_type: GameType, operands: Option<(i32, i32)>) -> Problem {
+        let profile = self.active_profile();
+        let (first, second) = (profile.operand1, profile.operand2);
+        let (a, b) = match operands {
+            Some(operands) => operands,
+            None => self.random_pair(first, second),
+        };
+        let signed = self.signed_numbers
+            && matches!(
+                game_type,
+                GameType::Addition
+                    | GameType::Subtraction
+                    | GameType::Multiplication
+                    | GameType::Division
+                    | GameType::DivisionWithRemainder
+            );
+        let (a, b) = if signed {
+            (self.random_sign(a), self.random_sign(b))
+        } else {
+            (a, b)
+        };
+
+        // math problems types to generate custom problems
+        match game_type {
+            GameType::Addition => Problem::new(
+                game_type,
+                Expr::binary(BinaryOp::Add, Expr::number(a), Expr::number(b)),
+                a.into(),
+                b.into(),
+            ),
+            GameType::Subtraction => Problem::new(
+                game_type,
+                Expr::binary(BinaryOp::Sub, Expr::number(a), Expr::number(b)),
+                a.into(),
+                b.into(),
+            ),
+            GameType::Multiplication => Problem::new(
+                game_type,
+                Expr::binary(BinaryOp::Mul, Expr::number(a), Expr::number(b)),
+                a.into(),
+                b.into(),
+            ),
             GameType::Division => {
                 let dividend = a * b;
                 Problem::new(
-                    format!("{} / {}", dividend, b),
-                    Answer::Integer(a),
-                    dividend,
-                    b,
+                    game_type,
+                    Expr::binary(BinaryOp::Div, Expr::number(dividend), Expr::number(b)),
+                    dividend.into(),
+                    b.into(),
+                )
+            }
+            GameType::DivisionWithRemainder => {
+                // a is the quotient, the divisor is at least 2 so there can be a remainder
+                let divisor = b.abs().max(2);
+                let remainder = self.rng.gen_range(0, divisor - 1);
+                let dividend = a * divisor + remainder;
+                Problem::new(
+                    game_type,
+                    Expr::binary(
+                        BinaryOp::DivRem,
+                        Expr::number(dividend),
+                        Expr::number(divisor),
+                    ),
+                    dividend.into(),
+                    divisor.into(),
                 )
             }
             GameType::FractionAddition => {
                 // Get second pair of numbers for second fraction
-                #[cfg(test)]
-                let (c, d) = (self.rng)(min, max);
-
-                #[cfg(not(test))]
-                let (c, d) = {
-                    let mut rng = rand::thread_rng();
-                    (rng.gen_range(min..=max), rng.gen_range(min..=max))
-                };
+                let (c, d) = self.random_pair(first, second);
 
                 Problem::new(
-                    format!("{}/{} + {}/{}", a, b, c, d),
-                    Answer::Fraction {
-                        numerator: a * d + b * c,
-                        denominator: b * d,
-                    },
-                    a,
-                    b,
+                    game_type,
+                    Expr::binary(BinaryOp::Add, Expr::fraction(a, b), Expr::fraction(c, d)),
+                    a.into(),
+                    b.into(),
                 )
             }
             GameType::FractionSubtraction => {
-                #[cfg(test)]
-                let (c, d) = (self.rng)(min, max);
+                let (c, d) = self.random_pair(first, second);
+
+                Problem::new(
+                    game_type,
+                    Expr::binary(BinaryOp::Sub, Expr::fraction(a, b), Expr::fraction(c, d)),
+                    a.into(),
+                    b.into(),
+                )
+            }
+            GameType::FractionMultiplication => {
+                let (c, d) = self.random_pair(first, second);
+
+                Problem::new(
+                    game_type,
+                    Expr::binary(BinaryOp::Mul, Expr::fraction(a, b), Expr::fraction(c, d)),
+                    a.into(),
+                    b.into(),
+                )
+            }
+            GameType::FractionDivision => {
+                // the divisor fraction can never be zero
+                let c = self.random_nonzero(first.min, first.max);
+                let d = self.rng.gen_range(second.min, second.max);
 
-                #[cfg(not(test))]
-                let (c, d) = {
-                    let mut rng = rand::thread_rng();
-                    (rng.gen_range(min..=max), rng.gen_range(min..=max))
+                Problem::new(
+                    game_type,
+                    Expr::binary(BinaryOp::Div, Expr::fraction(a, b), Expr::fraction(c, d)),
+                    a.into(),
+                    b.into(),
+                )
+            }
+            GameType::DecimalAddition
+            | GameType::DecimalSubtraction
+            | GameType::DecimalMultiplication => {
+                let op = match game_type {
+                    GameType::DecimalAddition => BinaryOp::Add,
+                    GameType::DecimalSubtraction => BinaryOp::Sub,
+                    _ => BinaryOp::Mul,
                 };
+                let x = self.random_decimal(first);
+                let y = self.random_decimal(second);
 
                 Problem::new(
-                    format!("{}/{} - {}/{}", a, b, c, d),
-                    Answer::Fraction {
-                        numerator: a * d - b * c,
-                        denominator: b * d,
-                    },
-                    a,
-                    b,
+                    game_type,
+                    Expr::binary(op, Expr::Decimal(x), Expr::Decimal(y)),
+                    x.mantissa(),
+                    y.mantissa(),
                 )
             }
-        };
+            GameType::DecimalDivision => {
+                // like integer division the dividend is built from the quotient so it is exact
+                let quotient = self.random_decimal(first);
+                let divisor = self.random_decimal(second);
+                let dividend = quotient
+                    .checked_mul(divisor)
+                    .expect("decimal operands are small enough to multiply");
 
-        // se the current problem to the generated problem
-        self.current_problem = Some(problem.clone());
+                Problem::new(
+                    game_type,
+                    Expr::binary(
+                        BinaryOp::Div,
+                        Expr::Decimal(dividend),
+                        Expr::Decimal(divisor),
+                    ),
+                    dividend.mantissa(),
+                    divisor.mantissa(),
+                )
+            }
+            GameType::Chain => {
+                // parentheses are introduced from the hard level on
+                let parentheses = matches!(
+                    self.current_difficulty,
+                    GameDifficulty::Hard | GameDifficulty::Mastery
+                );
+                let span = profile.span();
+                let expr = chain::generate_chain(
+                    self.rng.as_mut(),
+                    self.chain_operands,
+                    span.min,
+                    span.max,
+                    parentheses,
+                    self.signed_numbers,
+                );
+                let numbers = expr.numbers();
+                Problem::new(game_type, expr, numbers[0], numbers[1])
+            }
+            GameType::Mixed => unreachable!("mixed rounds pick a concrete operation first"),
+        }
+    }
 
-        problem
+    fn random_pair(&mut self, first: OperandRange, second: OperandRange) -> (i32, i32) {
+        let a = self.rng.gen_range(first.min, first.max);
+        let b = self.rng.gen_range(second.min, second.max);
+        (a, b)
+    }
+
+    // random_decimal draws from the range with the difficulty's number of decimal places
+    fn random_decimal(&mut self, range: OperandRange) -> Decimal {
+        let places = self.decimal_places(self.active_profile().difficulty);
+        let scale = 10i32.pow(places);
+        let mantissa = self.random_nonzero(range.min * scale, range.max * scale);
+        Decimal::new(mantissa as i64, places).expect("decimal places are small")
+    }
+
+    pub fn decimal_places(&self, difficulty: GameDifficulty) -> u32 {
+        self.decimal_places
+            .get(&difficulty)
+            .copied()
+            .unwrap_or(1)
+            .min(MAX_DECIMAL_PLACES)
+    }
+
+    fn random_sign(&mut self, n: i32) -> i32 {
+        if self.rng.gen_range(0, 1) == 1 {
+            -n
+        } else {
+            n
+        }
+    }
+
+    fn random_nonzero(&mut self, min: i32, max: i32) -> i32 {
+        if min == 0 && max == 0 {
+            return 1;
+        }
+        loop {
+            let n = self.rng.gen_range(min, max);
+            if n != 0 {
+                return n;
+            }
+        }
+    }
+
+    // active_profile is the profile problems are drawn from, the level of the game type in
+    // adaptive mode and the chosen difficulty otherwise
+    fn active_profile(&self) -> DifficultyProfile {
+        match self.skill_level() {
+            Some(_) => self.skill().profile(),
+            None => self.profile(self.current_difficulty),
+        }
+    }
+
+    fn skill(&self) -> Staircase {
+        self.skills
+            .get(&self.current_type)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // skill_level is the adaptive level of the current game type
+    pub fn skill_level(&self) -> Option<u32> {
+        (self.adaptive && self.placement.is_none()).then(|| self.skill().level())
+    }
+
+    fn record_skill(&mut self, correct: bool, latency: chrono::Duration) {
+        if self.skill_level().is_some() {
+            self.skills
+                .entry(self.current_type)
+                .or_default()
+                .record(correct, latency);
+        }
+    }
+
+    // profiles lists the presets followed by the custom profiles
+    pub fn profiles(&self) -> Vec<DifficultyProfile> {
+        let mut profiles = difficulty::presets();
+        profiles.extend(self.custom_profiles.iter().cloned());
+        profiles
+    }
+
+    // profile falls back to the basic preset for a custom profile that was removed
+    pub fn profile(&self, difficulty: GameDifficulty) -> DifficultyProfile {
+        let profiles = self.profiles();
+        let fallback = profiles[0].clone();
+        profiles
+            .into_iter()
+            .find(|profile| profile.difficulty == difficulty)
+            .unwrap_or(fallback)
     }
 
-    fn generate_range(&self, difficulty: &GameDifficulty) -> (i32, i32) {
-        match difficulty {
-            GameDifficulty::Basic => (1, 9),
-            GameDifficulty::Medium => (10, 99),
-            GameDifficulty::Hard => (100, 999),
-            GameDifficulty::Mastery => (1000, 10000),
+    // add_profile copies the ranges of an existing profile so it can be tweaked from there
+    pub fn add_profile(&mut self, name: &str, from: GameDifficulty) -> GameDifficulty {
+        let difficulty = GameDifficulty::Custom(self.next_profile_id);
+        self.next_profile_id += 1;
+        let from = self.profile(from);
+        self.custom_profiles.push(DifficultyProfile {
+            difficulty,
+            name: name.to_string(),
+            operand1: from.operand1,
+            operand2: from.operand2,
+            complexity: from.complexity,
+        });
+        difficulty
+    }
+
+    pub fn remove_profile(&mut self, difficulty: GameDifficulty) {
+        self.custom_profiles
+            .retain(|profile| profile.difficulty != difficulty);
+        if self.current_difficulty == difficulty {
+            self.current_difficulty = GameDifficulty::Basic;
         }
     }
 
+    // check_answer checks an answer while it is typed, only a wrong answer with as many digits
+    // as the expected one is an attempt
     pub fn check_answer(&mut self, user_answer: &Answer) -> bool {
+        self.answer(user_answer, false)
+    }
+
+    // submit_answer checks an answer the player is done with, a wrong one is always an attempt
+    pub fn submit_answer(&mut self, user_answer: &Answer) -> bool {
+        self.answer(user_answer, true)
+    }
+
+    fn answer(&mut self, user_answer: &Answer, submitted: bool) -> bool {
         if let Some(problem) = &mut self.current_problem {
             let correct = problem.check_answer(user_answer);
-            if correct {
-                self.score += 1;
-                self.current_round_completed_problems += 1;
+            let complete = match (&problem.answer, user_answer) {
+                // the remainder is still to come, 7 is on the way to 7 r 4
+                (Answer::QuotientRemainder { .. }, Answer::Integer(_)) => false,
+                _ => {
+                    count_digits(&user_answer.to_string())
+                        >= count_digits(&problem.answer.to_string())
+                }
+            };
+            if correct || complete || submitted {
+                self.record_attempt(user_answer.clone(), correct);
             }
             correct
         } else {
@@ -236,60 +1009,426 @@ impl Game {
         }
     }
 
+    // check_fraction_answer applies the round's fraction policy on top of the value check
+    pub fn check_fraction_answer(&mut self, written: &WrittenFraction) -> Verdict {
+        self.answer_fraction(written, false)
+    }
+
+    pub fn submit_fraction_answer(&mut self, written: &WrittenFraction) -> Verdict {
+        self.answer_fraction(written, true)
+    }
+
+    fn answer_fraction(&mut self, written: &WrittenFraction, submitted: bool) -> Verdict {
+        let Some(problem) = &mut self.current_problem else {
+            return Verdict::Incorrect;
+        };
+
+        let verdict = written.verdict(&problem.answer, self.fraction_policy);
+        // the denominator is typed last, a wrong value is an attempt once it has as many digits
+        // as the expected one. A whole part alone may be the start of a mixed number. The right
+        // value in the wrong form is not a mistake.
+        let complete = match &problem.answer {
+            Answer::Fraction(expected) => {
+                !written.whole_only
+                    && count_digits(&written.denominator.to_string())
+                        >= count_digits(&expected.denominator().to_string())
+            }
+            _ => true,
+        };
+        if verdict.is_correct() {
+            problem.solved_at = Some(chrono::Utc::now());
+        }
+        let attempted = match verdict {
+            Verdict::Correct => true,
+            Verdict::Incorrect => complete || submitted,
+            Verdict::NotLowestTerms | Verdict::NotMixedNumber => false,
+        };
+        if let (true, Some(value)) = (attempted, written.value()) {
+            self.record_attempt(Answer::Fraction(value), verdict.is_correct());
+        }
+        verdict
+    }
+
+    // wrong_attempts is how many wrong answers the current problem got
+    pub fn wrong_attempts(&self) -> usize {
+        self.current_problem.as_ref().map_or(0, |problem| {
+            problem
+                .attempts
+                .iter()
+                .filter(|attempt| !attempt.correct)
+                .count()
+        })
+    }
+
+    // record_attempt adds the answer to the problem's attempts and counts it
+    fn record_attempt(&mut self, value: Answer, correct: bool) {
+        let Some(problem) = &mut self.current_problem else {
+            return;
+        };
+        let first_miss = !correct && !problem.is_missed();
+        problem.attempts.push(Attempt {
+            value,
+            at: chrono::Utc::now(),
+            correct,
+        });
+        if correct {
+            self.record_correct_answer();
+        } else {
+            self.record_mistake(first_miss);
+        }
+    }
+
+    // record_mistake applies the penalties of every wrong answer, the schedule, ratings and
+    // levels only count the first one of a problem
+    fn record_mistake(&mut self, first_miss: bool) {
+        // the score stays at 0 or above
+        let penalty = (self.scoring.mistake_penalty as i32).min(self.score.max(0));
+        self.score -= penalty;
+        self.round_score.penalties += penalty;
+        self.streak = 0;
+        if self.lives.is_some() && self.placement.is_none() && self.game_state == GameState::Playing
+        {
+            self.lives_left = self.lives_left.saturating_sub(1);
+            if self.lives_left == 0 {
+                self.game_state = GameState::OutOfLives;
+            }
+        }
+        if !first_miss {
+            return;
+        }
+        let Some(problem) = &self.current_problem else {
+            return;
+        };
+        let now = chrono::Utc::now();
+        let latency = now - problem.created_at;
+        if let Some(fact) = Fact::of(problem) {
+            self.schedule.record(fact, false, latency, now);
+        }
+        self.record_rating(false);
+        self.record_skill(false, latency);
+        self.record_placement(false, latency);
+    }
+
+    fn record_rating(&mut self, correct: bool) {
+        let difficulty = self.active_profile().difficulty;
+        if let Some(problem) = &self.current_problem {
+            self.ratings
+                .record(self.current_type, difficulty, problem, correct);
+        }
+    }
+
+    // rating is the player's rating at the current game type and difficulty
+    pub fn rating(&self) -> f64 {
+        self.ratings
+            .player(self.current_type, self.active_profile().difficulty)
+    }
+
+    // start_placement runs the placement test as a round of its own, it asks plain problems
+    // so a focus or a review is left
+    pub fn start_placement(&mut self) {
+        self.focus = None;
+        self.review = false;
+        self.placement = Some(Placement::new(self.current_type, self.current_difficulty));
+        self.score = 0;
+        self.current_round_completed_problems = 0;
+        self.round_problems.clear();
+        self.round_history.clear();
+        self.round_score = ScoreBreakdown::default();
+        self.streak = 0;
+        self.game_state = GameState::Playing;
+        self.generate_problem();
+    }
+
+    pub fn placement_progress(&self) -> Option<(usize, usize)> {
+        self.placement.as_ref().map(Placement::progress)
+    }
+
+    fn record_placement(&mut self, correct: bool, latency: chrono::Duration) {
+        let Some(placement) = &mut self.placement else {
+            return;
+        };
+        placement.record(correct, latency);
+        if placement.next().is_none() {
+            self.finish_placement();
+        }
+    }
+
+    // finish_placement keeps the results, starts the adaptive levels from them and goes back
+    // to the game type of before at its placed level. Mixed rounds are not placed, they go back
+    // to the level of before.
+    fn finish_placement(&mut self) {
+        let Some(placement) = self.placement.take() else {
+            return;
+        };
+        for (game_type, result) in placement.results() {
+            self.skills
+                .insert(game_type, Staircase::for_preset(result.difficulty));
+            self.placements.insert(game_type, result);
+        }
+        self.current_type = placement.return_type;
+        self.current_difficulty = self
+            .placements
+            .get(&self.current_type)
+            .map_or(placement.return_difficulty, |result| result.difficulty);
+        self.current_round_completed_problems = 0;
+        self.game_state = GameState::NotStarted;
+    }
+
+    // cancel_placement stops a running test without keeping any results and goes back to the
+    // level of before
+    pub fn cancel_placement(&mut self) {
+        let Some(placement) = self.placement.take() else {
+            return;
+        };
+        self.current_type = placement.return_type;
+        self.current_difficulty = placement.return_difficulty;
+        self.current_round_completed_problems = 0;
+        self.game_state = GameState::NotStarted;
+        self.generate_problem();
+    }
+
+    fn record_correct_answer(&mut self) {
+        let mut first_try = None;
+        if let Some(problem) = self.current_problem.as_ref().filter(|p| !p.is_missed()) {
+            let solved_at = problem.solved_at.unwrap_or_else(chrono::Utc::now);
+            let latency = solved_at - problem.created_at;
+            if let Some(fact) = Fact::of(problem) {
+                self.schedule.record(fact, true, latency, solved_at);
+            }
+            self.record_rating(true);
+            self.record_skill(true, latency);
+            first_try = Some(latency);
+        }
+        if let Some(problem) = &self.current_problem {
+            self.round_history.push(problem.clone());
+        }
+        self.streak += 1;
+        let latency = self
+            .current_problem
+            .as_ref()
+            .and_then(Problem::latency)
+            .unwrap_or_else(chrono::Duration::zero);
+        let award = self
+            .scoring
+            .award(latency, self.streak, self.active_profile().difficulty);
+        self.score += award.total();
+        self.round_score.add(award);
+        self.current_round_completed_problems += 1;
+        // the deck moves on once its fact is answered, a fact that was dealt and then replaced
+        // by another problem comes up again
+        if self.current_problem.as_ref().is_some_and(|p| p.dealt) {
+            let key = (self.current_type, self.current_difficulty);
+            if let Some(deck) = self.decks.get_mut(&key) {
+                deck.answered();
+            }
+        }
+        // a focus only counts the problems it picked
+        let focused = self.current_problem.as_ref().is_some_and(|p| p.focused);
+        let score = match self.focus.as_ref().filter(|_| focused) {
+            Some(focus) => self
+                .focus_scores
+                .entry((self.current_type, focus.clone()))
+                .or_insert(0),
+            None => self
+                .level_scores
+                .entry((self.current_type, self.current_difficulty))
+                .or_insert(0),
+        };
+        *score += 1;
+        if let Some(latency) = first_try {
+            self.record_placement(true, latency);
+        }
+    }
+
+    pub fn focus_score(&self, game_type: GameType, focus: &Focus) -> u32 {
+        self.focus_scores
+            .get(&(game_type, focus.clone()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn level_score(&self, game_type: GameType, difficulty: GameDifficulty) -> u32 {
+        self.level_scores
+            .get(&(game_type, difficulty))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    // round_accuracy is how many of the round's attempts were right out of how many, the
+    // current problem counts while it is not solved yet
+    pub fn round_accuracy(&self) -> (usize, usize) {
+        let unsolved = self
+            .current_problem
+            .iter()
+            .filter(|problem| problem.solved_at.is_none());
+        let attempts: Vec<&Attempt> = self
+            .round_history
+            .iter()
+            .chain(unsolved)
+            .flat_map(|problem| &problem.attempts)
+            .collect();
+        let correct = attempts.iter().filter(|attempt| attempt.correct).count();
+        (correct, attempts.len())
+    }
+
+    pub fn round_stats(&self) -> Option<RoundStats> {
+        let latencies: Vec<chrono::Duration> = self
+            .round_history
+            .iter()
+            .filter_map(Problem::latency)
+            .collect();
+        RoundStats::of(&latencies)
+    }
+
     pub fn is_round_completed(&self) -> bool {
-        self.current_round_completed_problems == self.problems_per_round
+        // the placement test takes as many problems as it needs and sprints end with the clock
+        self.placement.is_none()
+            && self.sprint_seconds.is_none()
+            && self.current_round_completed_problems == self.problems_per_round
     }
 }
 
+// Problem is built from an expression tree, the text and the answer are derived from it
 #[derive(Debug, PartialEq, Clone)]
 pub struct Problem {
+    pub game_type: GameType,
+    pub expr: Expr,
     pub problem: String,
     pub answer: Answer,
-    pub operand1: i32,
-    pub operand2: i32,
+    // complexity counts the carries, borrows and partial products the problem takes
+    pub complexity: Complexity,
+    // focused tells whether the focus drill picked the operands, game types it doesn't apply
+    // to are drawn from the difficulty as usual
+    pub focused: bool,
+    // dealt tells whether the problem is the fact on top of the deck
+    pub dealt: bool,
+    // operand1 and operand2 are the drawn operands, a decimal by its mantissa
+    pub operand1: i64,
+    pub operand2: i64,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub solved_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub attempts: Vec<Attempt>,
 }
 
 impl Problem {
-    pub fn new(problem: String, answer: Answer, operand1: i32, operand2: i32) -> Self {
+    pub fn new(game_type: GameType, expr: Expr, operand1: i64, operand2: i64) -> Self {
+        let answer = expr
+            .evaluate()
+            .expect("generated problems never divide by zero or overflow");
         Self {
-            problem,
+            game_type,
+            problem: expr.to_string(),
+            complexity: Complexity::of(&expr),
+            focused: false,
+            dealt: false,
+            expr,
             answer,
             operand1,
             operand2,
             created_at: chrono::Utc::now(),
             solved_at: None,
+            attempts: Vec::new(),
         }
     }
 
-    pub fn check_answer(&mut self, user_answer: &Answer) -> bool {
-        let correct = self.answer.check(user_answer);
-        if correct {
-            self.solved_at = Some(chrono::Utc::now());
+    // hide_operand turns the problem around: the operand at index is asked for and the result
+    // is shown. An index past the last operand leaves the result as the unknown.
+    pub fn hide_operand(self, index: usize) -> Self {
+        let Some((expr, hidden)) = self.expr.hide_operand(index) else {
+            return self;
+        };
+        let answer = hidden.evaluate().expect("literal operands always evaluate");
+        Self {
+            problem: format!("{} = {}", expr, self.answer),
+            complexity: Complexity::of_missing(&expr),
+            expr,
+            answer,
+            ..self
         }
-        correct
+    }
+
+    // is_missed tells whether any answer to the problem was wrong
+    pub fn is_missed(&self) -> bool {
+        self.attempts.iter().any(|attempt| !attempt.correct)
+    }
+
+    // latency is how long the problem took to solve
+    pub fn latency(&self) -> Option<chrono::Duration> {
+        Some(self.solved_at? - self.created_at)
+    }
+
+    pub fn check_answer(&mut self, user_answer: &Answer) -> bool {
+        let correct = self.answer.check(user_answer);
+        if correct {
+            self.solved_at = Some(chrono::Utc::now());
+        }
+        correct
     }
 }
 
 #[cfg(test)]
+// the original tests compare with true and false
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
+    use crate::complexity;
 
     #[test]
-    fn test_generate_range() {
+    fn test_preset_profiles() {
         let game = Game::new();
-        assert_eq!(game.generate_range(&GameDifficulty::Basic), (1, 9));
-        assert_eq!(game.generate_range(&GameDifficulty::Medium), (10, 99));
-        assert_eq!(game.generate_range(&GameDifficulty::Hard), (100, 999));
-        assert_eq!(game.generate_range(&GameDifficulty::Mastery), (1000, 10000));
+        let range = |difficulty| {
+            let profile = game.profile(difficulty);
+            (profile.operand1.min, profile.operand2.max)
+        };
+        assert_eq!(range(GameDifficulty::Basic), (1, 9));
+        assert_eq!(range(GameDifficulty::Medium), (10, 99));
+        assert_eq!(range(GameDifficulty::Hard), (100, 999));
+        // mastery used to reach 10000, a five digit operand
+        assert_eq!(range(GameDifficulty::Mastery), (1000, 9999));
+    }
+
+    #[test]
+    fn test_custom_profile() {
+        let mut game = Game::with_seed(12);
+        let difficulty = game.add_profile("3-digit × 1-digit", GameDifficulty::Hard);
+        game.custom_profiles[0].operand2 = OperandRange::digits(1);
+        game.current_type = GameType::Multiplication;
+        game.current_difficulty = difficulty;
+
+        for _ in 0..100 {
+            let problem = game.generate_problem().unwrap();
+            assert!(
+                (100..=999).contains(&problem.operand1),
+                "{}",
+                problem.problem
+            );
+            assert!((1..=9).contains(&problem.operand2), "{}", problem.problem);
+        }
+
+        assert_eq!(
+            game.add_profile("copy", difficulty),
+            GameDifficulty::Custom(1)
+        );
+        assert_eq!(game.profile(GameDifficulty::Custom(1)).operand2.max, 9);
+
+        game.remove_profile(difficulty);
+        assert_eq!(game.current_difficulty, GameDifficulty::Basic);
+        assert_eq!(game.profile(difficulty).name, "Basic");
+        assert_eq!(game.profiles().len(), 5);
+
+        // the id of the newest profile isn't given out again once it is removed
+        game.remove_profile(GameDifficulty::Custom(1));
+        assert_eq!(
+            game.add_profile("new", GameDifficulty::Hard),
+            GameDifficulty::Custom(2)
+        );
     }
 
     #[test]
     fn test_range_in_generate_problem_addition() {
         // Addition basic
         let mut game = Game::new();
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 1 && problem.operand1 < 10,
             "Addition basic Operand1: {}",
@@ -303,7 +1442,7 @@ mod tests {
 
         // set level to medium
         game.current_difficulty = GameDifficulty::Medium;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 10 && problem.operand1 < 100,
             "Addition medium Operand1: {}",
@@ -317,7 +1456,7 @@ mod tests {
 
         // set level to hard
         game.current_difficulty = GameDifficulty::Hard;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 100 && problem.operand1 < 1000,
             "Addition hard Operand1: {}",
@@ -331,7 +1470,7 @@ mod tests {
 
         // set level to mastery
         game.current_difficulty = GameDifficulty::Mastery;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 1000 && problem.operand1 <= 10000,
             "Addition mastery Operand1: {}",
@@ -350,7 +1489,7 @@ mod tests {
         let mut game = Game::new();
         // set game to substraction
         game.current_type = GameType::Subtraction;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 1 && problem.operand1 < 10,
             "Substraction basic Operand1: {}",
@@ -364,7 +1503,7 @@ mod tests {
 
         // set level to medium
         game.current_difficulty = GameDifficulty::Medium;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 10 && problem.operand1 < 100,
             "Substraction medium Operand1: {}",
@@ -378,7 +1517,7 @@ mod tests {
 
         // set level to hard
         game.current_difficulty = GameDifficulty::Hard;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 100 && problem.operand1 < 1000,
             "Substraction hard Operand1: {}",
@@ -392,7 +1531,7 @@ mod tests {
 
         // set level to mastery
         game.current_difficulty = GameDifficulty::Mastery;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 1000 && problem.operand1 <= 10000,
             "Substraction mastery Operand1: {}",
@@ -411,7 +1550,7 @@ mod tests {
         let mut game = Game::new();
         // set game to multiplication
         game.current_type = GameType::Multiplication;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 1 && problem.operand1 < 10,
             "Multiplication basic Operand1: {}",
@@ -424,7 +1563,7 @@ mod tests {
         );
 
         game.current_difficulty = GameDifficulty::Medium;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 10 && problem.operand1 < 100,
             "Multiplication medium Operand1: {}",
@@ -438,7 +1577,7 @@ mod tests {
 
         // set level to hard
         game.current_difficulty = GameDifficulty::Hard;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 100 && problem.operand1 < 1000,
             "Multiplication hard Operand1: {}",
@@ -452,7 +1591,7 @@ mod tests {
 
         // set level to mastery
         game.current_difficulty = GameDifficulty::Mastery;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand1 >= 1000 && problem.operand1 <= 10000,
             "Multiplication mastery Operand1: {}",
@@ -474,7 +1613,7 @@ mod tests {
         let mut game = Game::new();
         // set game to division
         game.current_type = GameType::Division;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand2 >= 1 && problem.operand2 <= 10,
             "Division basic divisor: {}",
@@ -489,7 +1628,7 @@ mod tests {
         // division medium (range 10-100)
         // set level to medium
         game.current_difficulty = GameDifficulty::Medium;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand2 >= 10 && problem.operand2 <= 100,
             "Division medium divisor: {}",
@@ -505,7 +1644,7 @@ mod tests {
         // division hard (range 100-1000)
         // set level to hard
         game.current_difficulty = GameDifficulty::Hard;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand2 >= 100 && problem.operand2 <= 1000,
             "Division hard divisor: {}",
@@ -520,7 +1659,7 @@ mod tests {
         // division mastery (range 1000-10000)
         // set level to mastery
         game.current_difficulty = GameDifficulty::Mastery;
-        let problem = game.generate_problem();
+        let problem = game.generate_problem().unwrap();
         assert!(
             problem.operand2 >= 1000 && problem.operand2 <= 10000,
             "Division mastery divisor: {}",
@@ -535,8 +1674,8 @@ mod tests {
 
     #[test]
     fn test_addition_basic_problem() {
-        let mut game = Game::with_mock_rng(|_,_| (2, 3));
-        let mut problem = game.generate_problem();
+        let mut game = Game::with_mock_rng(|_, _| (2, 3));
+        let mut problem = game.generate_problem().unwrap();
 
         assert_eq!(problem.problem, "2 + 3");
         assert_eq!(problem.check_answer(&Answer::Integer(5)), true);
@@ -545,12 +1684,1153 @@ mod tests {
 
     #[test]
     fn test_addition_medium_problem() {
-        let mut game = Game::with_mock_rng(|_,_| (5, 6));
+        let mut game = Game::with_mock_rng(|_, _| (5, 6));
         game.current_difficulty = GameDifficulty::Medium;
-        let mut problem = game.generate_problem();
+        let mut problem = game.generate_problem().unwrap();
 
         assert_eq!(problem.problem, "5 + 6");
         assert_eq!(problem.check_answer(&Answer::Integer(11)), true);
         assert_eq!(problem.check_answer(&Answer::Integer(12)), false);
     }
+
+    #[test]
+    fn test_same_seed_reproduces_round_for_every_game_type() {
+        let game_types = [
+            GameType::Addition,
+            GameType::Subtraction,
+            GameType::Multiplication,
+            GameType::Division,
+            GameType::DivisionWithRemainder,
+            GameType::FractionAddition,
+            GameType::FractionSubtraction,
+            GameType::FractionMultiplication,
+            GameType::FractionDivision,
+            GameType::DecimalAddition,
+            GameType::DecimalSubtraction,
+            GameType::DecimalMultiplication,
+            GameType::DecimalDivision,
+            GameType::Chain,
+        ];
+
+        for game_type in game_types {
+            let mut first = Game::with_seed(1234);
+            let mut second = Game::with_seed(1234);
+            first.current_type = game_type;
+            second.current_type = game_type;
+
+            for _ in 0..first.problems_per_round {
+                let a = first.generate_problem().unwrap();
+                let b = second.generate_problem().unwrap();
+                assert_eq!(a.problem, b.problem);
+                assert_eq!(a.answer, b.answer);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reseed_restarts_sequence() {
+        let mut game = Game::with_seed(99);
+        let first: Vec<String> = (0..5)
+            .map(|_| game.generate_problem().unwrap().problem)
+            .collect();
+
+        game.reseed(99);
+        let replay: Vec<String> = (0..5)
+            .map(|_| game.generate_problem().unwrap().problem)
+            .collect();
+
+        assert_eq!(game.seed(), 99);
+        assert_eq!(first, replay);
+    }
+
+    #[test]
+    fn test_fraction_problem_is_built_from_expression() {
+        let mut game = Game::with_mock_rng(|_, _| (1, 2));
+        game.current_type = GameType::FractionAddition;
+        let problem = game.generate_problem().unwrap();
+
+        assert_eq!(problem.problem, "1/2 + 1/2");
+        assert_eq!(problem.problem, problem.expr.to_string());
+        assert_eq!(problem.expr.evaluate(), Some(problem.answer.clone()));
+        assert!(problem
+            .answer
+            .check(&Answer::Fraction(Rational::from_integer(1))));
+        assert!(problem
+            .answer
+            .check(&Answer::Fraction(Rational::new(4, 4).unwrap())));
+    }
+
+    #[test]
+    fn test_chain_problem() {
+        let mut game = Game::with_seed(5);
+        game.current_type = GameType::Chain;
+        game.chain_operands = 5;
+
+        for difficulty in [GameDifficulty::Basic, GameDifficulty::Hard] {
+            game.current_difficulty = difficulty;
+            let max = game.profile(difficulty).span().max;
+            for _ in 0..50 {
+                let problem = game.generate_problem().unwrap();
+                assert_eq!(problem.expr.numbers().len(), 5, "{}", problem.problem);
+                assert!(
+                    problem.answer >= Answer::Integer(0)
+                        && problem.answer <= Answer::Integer(max * max),
+                    "{} = {}",
+                    problem.problem,
+                    problem.answer
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_mixed_round_uses_only_chosen_operations() {
+        let mut game = Game::with_seed(8);
+        game.current_type = GameType::Mixed;
+        game.mixed_operations = vec![
+            (GameType::Addition, 1),
+            (GameType::Subtraction, 0),
+            (GameType::Multiplication, 3),
+        ];
+
+        let mut additions = 0;
+        let mut multiplications = 0;
+        for i in 0..400 {
+            // a round never repeats a problem, so start a new one every few problems
+            if i % 10 == 0 {
+                game.reseed(i);
+            }
+            let problem = game.generate_problem().unwrap();
+            match problem.game_type {
+                GameType::Addition => additions += 1,
+                GameType::Multiplication => multiplications += 1,
+                other => panic!("unexpected operation {:?}", other),
+            }
+            assert_eq!(game.current_problem, Some(problem));
+        }
+        assert!(multiplications > additions * 2);
+
+        assert!(game.check_answer(&game.current_problem.clone().unwrap().answer));
+        assert_eq!(game.level_score(GameType::Mixed, GameDifficulty::Basic), 1);
+        assert_eq!(
+            game.level_score(GameType::Multiplication, GameDifficulty::Basic),
+            0
+        );
+    }
+
+    #[test]
+    fn test_mixed_round_without_weights_falls_back_to_addition() {
+        let mut game = Game::with_seed(8);
+        game.current_type = GameType::Mixed;
+        game.mixed_operations = vec![(GameType::Division, 0)];
+        assert_eq!(
+            game.generate_problem().unwrap().game_type,
+            GameType::Addition
+        );
+    }
+
+    #[test]
+    fn test_fraction_multiplication_and_division() {
+        let mut game = Game::with_mock_rng(|_, _| (2, 3));
+        game.current_type = GameType::FractionMultiplication;
+        let problem = game.generate_problem().unwrap();
+        assert_eq!(problem.problem, "2/3 * 2/3");
+        assert!(problem
+            .answer
+            .check(&Answer::Fraction(Rational::new(8, 18).unwrap())));
+
+        game.current_type = GameType::FractionDivision;
+        let problem = game.generate_problem().unwrap();
+        assert_eq!(problem.problem, "2/3 ÷ 2/3");
+        assert!(problem
+            .answer
+            .check(&Answer::Fraction(Rational::new(6, 6).unwrap())));
+    }
+
+    #[test]
+    fn test_fraction_division_never_divides_by_zero() {
+        let mut game = Game::with_seed(21);
+        game.current_type = GameType::FractionDivision;
+        for difficulty in [
+            GameDifficulty::Basic,
+            GameDifficulty::Medium,
+            GameDifficulty::Hard,
+            GameDifficulty::Mastery,
+        ] {
+            game.current_difficulty = difficulty;
+            for _ in 0..100 {
+                let problem = game.generate_problem().unwrap();
+                assert!(matches!(problem.answer, Answer::Fraction(_)));
+            }
+        }
+    }
+
+    fn written(whole: i64, numerator: i64, denominator: i64) -> WrittenFraction {
+        WrittenFraction {
+            whole,
+            numerator,
+            denominator,
+            whole_only: false,
+        }
+    }
+
+    #[test]
+    fn test_written_fraction_parse() {
+        assert_eq!(WrittenFraction::parse("", "2", "4"), Some(written(0, 2, 4)));
+        assert_eq!(
+            WrittenFraction::parse("1", "1", "2"),
+            Some(written(1, 1, 2))
+        );
+        assert_eq!(
+            WrittenFraction::parse("3", "", ""),
+            Some(WrittenFraction {
+                whole_only: true,
+                ..written(3, 0, 1)
+            })
+        );
+        assert_eq!(WrittenFraction::parse("", "", ""), None);
+        assert_eq!(WrittenFraction::parse("", "1", "0"), None);
+        assert_eq!(WrittenFraction::parse("1", "-1", "2"), None);
+        assert_eq!(
+            written(-1, 1, 2).value(),
+            Some(Rational::new(-3, 2).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_fraction_policies() {
+        let half = Answer::Fraction(Rational::new(1, 2).unwrap());
+        let three_halves = Answer::Fraction(Rational::new(3, 2).unwrap());
+
+        let any = FractionPolicy::AnyEquivalent;
+        assert_eq!(written(0, 2, 4).verdict(&half, any), Verdict::Correct);
+        assert_eq!(
+            written(0, 6, 4).verdict(&three_halves, any),
+            Verdict::Correct
+        );
+        assert_eq!(written(0, 1, 3).verdict(&half, any), Verdict::Incorrect);
+
+        let lowest = FractionPolicy::LowestTerms;
+        assert_eq!(written(0, 1, 2).verdict(&half, lowest), Verdict::Correct);
+        assert_eq!(
+            written(0, 2, 4).verdict(&half, lowest),
+            Verdict::NotLowestTerms
+        );
+        assert_eq!(
+            written(0, 3, 2).verdict(&three_halves, lowest),
+            Verdict::Correct
+        );
+        assert_eq!(written(0, 2, 5).verdict(&half, lowest), Verdict::Incorrect);
+
+        let mixed = FractionPolicy::MixedNumber;
+        assert_eq!(
+            written(1, 1, 2).verdict(&three_halves, mixed),
+            Verdict::Correct
+        );
+        assert_eq!(
+            written(0, 3, 2).verdict(&three_halves, mixed),
+            Verdict::NotMixedNumber
+        );
+        assert_eq!(
+            written(1, 2, 4).verdict(&three_halves, mixed),
+            Verdict::NotLowestTerms
+        );
+        assert_eq!(written(0, 1, 2).verdict(&half, mixed), Verdict::Correct);
+        assert!(Verdict::NotLowestTerms.feedback().is_some());
+    }
+
+    #[test]
+    fn test_check_fraction_answer_uses_round_policy() {
+        let mut game = Game::with_mock_rng(|_, _| (1, 2));
+        game.current_type = GameType::FractionAddition;
+        game.fraction_policy = FractionPolicy::LowestTerms;
+        game.generate_problem();
+
+        // 1/2 + 1/2 = 1
+        assert_eq!(
+            game.check_fraction_answer(&written(0, 2, 2)),
+            Verdict::NotLowestTerms
+        );
+        assert_eq!(game.score, 0);
+        assert_eq!(
+            game.check_fraction_answer(&written(1, 0, 1)),
+            Verdict::Correct
+        );
+        assert_eq!(game.score, 1);
+        assert!(game.current_problem.unwrap().solved_at.is_some());
+    }
+
+    #[test]
+    fn test_mixed_number_whole_part_first() {
+        let mut game = Game::with_mock_rng(|_, _| (3, 2));
+        game.current_type = GameType::FractionMultiplication;
+        game.fraction_policy = FractionPolicy::MixedNumber;
+        game.generate_problem().unwrap();
+
+        // 3/2 * 3/2 = 2 1/4, the whole part alone is not a wrong answer yet
+        let whole = WrittenFraction::parse("2", "", "").unwrap();
+        assert_eq!(game.check_fraction_answer(&whole), Verdict::Incorrect);
+        assert_eq!(game.wrong_attempts(), 0);
+        let mixed = WrittenFraction::parse("2", "1", "4").unwrap();
+        assert_eq!(game.check_fraction_answer(&mixed), Verdict::Correct);
+        assert_eq!(game.wrong_attempts(), 0);
+
+        // a submitted whole part is an answer like any other
+        game.generate_problem().unwrap();
+        assert_eq!(game.submit_fraction_answer(&whole), Verdict::Incorrect);
+        assert_eq!(game.wrong_attempts(), 1);
+    }
+
+    #[test]
+    fn test_decimal_problems() {
+        let mut game = Game::with_mock_rng(|_, _| (25, 4));
+        game.current_type = GameType::DecimalAddition;
+        let mut problem = game.generate_problem().unwrap();
+        assert_eq!(problem.problem, "2.5 + 0.4");
+        assert!(problem.check_answer(&Answer::Decimal("2.90".parse().unwrap())));
+        assert!(!problem.check_answer(&Answer::Decimal("2.8".parse().unwrap())));
+
+        game.current_type = GameType::DecimalDivision;
+        let mut problem = game.generate_problem().unwrap();
+        assert_eq!(problem.problem, "1 / 0.4");
+        assert!(problem.check_answer(&Answer::Decimal("2.5".parse().unwrap())));
+    }
+
+    #[test]
+    fn test_decimal_places_per_difficulty() {
+        let mut game = Game::with_seed(17);
+        game.decimal_places.insert(GameDifficulty::Medium, 2);
+        game.current_difficulty = GameDifficulty::Medium;
+
+        for game_type in [
+            GameType::DecimalAddition,
+            GameType::DecimalSubtraction,
+            GameType::DecimalMultiplication,
+            GameType::DecimalDivision,
+        ] {
+            game.current_type = game_type;
+            let mut places = Vec::new();
+            for _ in 0..50 {
+                let problem = game.generate_problem().unwrap();
+                let answer = match problem.answer {
+                    Answer::Decimal(d) => d,
+                    other => panic!("{} = {:?}", problem.problem, other),
+                };
+                let numbers: Vec<Decimal> = problem
+                    .problem
+                    .split(' ')
+                    .filter_map(|token| token.parse().ok())
+                    .collect();
+                // the dividend is the product of the drawn quotient and divisor
+                let drawn = match game_type {
+                    GameType::DecimalDivision => [answer, numbers[1]],
+                    _ => [numbers[0], numbers[1]],
+                };
+                places.extend(drawn.iter().map(|number| number.scale()));
+            }
+            // trailing zeros are dropped, so some operands show fewer places
+            assert_eq!(places.iter().max(), Some(&2), "{:?}", game_type);
+        }
+    }
+
+    #[test]
+    fn test_long_decimal_operands() {
+        // the dividend of a mastery division has more digits than an i32 holds
+        let mut game = Game::with_seed(23);
+        game.decimal_places
+            .insert(GameDifficulty::Mastery, MAX_DECIMAL_PLACES);
+        game.current_difficulty = GameDifficulty::Mastery;
+        game.current_type = GameType::DecimalDivision;
+
+        for _ in 0..20 {
+            let problem = game.generate_problem().unwrap();
+            let dividend = match &problem.expr {
+                Expr::Binary(_, dividend, _) => match **dividend {
+                    Expr::Decimal(d) => d,
+                    _ => panic!("{}", problem.problem),
+                },
+                _ => panic!("{}", problem.problem),
+            };
+            assert_eq!(problem.operand1, dividend.mantissa());
+            assert!(problem.operand1 > i32::MAX as i64, "{}", problem.problem);
+        }
+    }
+
+    #[test]
+    fn test_division_with_remainder_problem() {
+        let mut game = Game::with_seed(4);
+        game.current_type = GameType::DivisionWithRemainder;
+        for _ in 0..100 {
+            let problem = game.generate_problem().unwrap();
+            let (dividend, divisor) = (problem.operand1, problem.operand2);
+            assert!((2..=9).contains(&divisor), "{}", problem.problem);
+            assert_eq!(
+                problem.answer,
+                Answer::QuotientRemainder {
+                    quotient: (dividend / divisor) as i32,
+                    remainder: (dividend % divisor) as i32
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_quotient_remainder() {
+        let expected = Some(Answer::QuotientRemainder {
+            quotient: 17,
+            remainder: 3,
+        });
+        assert_eq!(Answer::parse_quotient_remainder("17 r 3"), expected);
+        assert_eq!(Answer::parse_quotient_remainder("17r3"), expected);
+        assert_eq!(Answer::parse_quotient_remainder(" 17 R 3 "), expected);
+        assert_eq!(
+            Answer::parse_quotient_remainder("4"),
+            Some(Answer::Integer(4))
+        );
+        assert!(Answer::QuotientRemainder {
+            quotient: 4,
+            remainder: 0
+        }
+        .check(&Answer::Integer(4)));
+        let with_remainder = Answer::QuotientRemainder {
+            quotient: 17,
+            remainder: 3,
+        };
+        assert!(!with_remainder.check(&Answer::Integer(17)));
+        assert_eq!(Answer::parse_quotient_remainder("17 r"), None);
+        assert_eq!(Answer::parse_quotient_remainder("r 3"), None);
+    }
+
+    #[test]
+    fn test_typed_remainder_answer() {
+        // 7 then 7 then 4, the remainder is drawn from 0 to 6
+        let mut game = Game::with_mock_rng(|_, max| if max < 7 { (4, 4) } else { (7, 7) });
+        game.current_type = GameType::DivisionWithRemainder;
+        let problem = game.generate_problem().unwrap();
+        assert_eq!(problem.problem, "53 ÷ 7");
+
+        for typed in ["7", "7 r", "7 r 4"] {
+            if let Some(answer) = Answer::parse_quotient_remainder(typed) {
+                game.check_answer(&answer);
+            }
+        }
+        let attempts = &game.current_problem.as_ref().unwrap().attempts;
+        assert_eq!(
+            attempts.iter().map(|a| a.correct).collect::<Vec<_>>(),
+            vec![true]
+        );
+        assert_eq!(game.wrong_attempts(), 0);
+    }
+
+    #[test]
+    fn test_signed_numbers() {
+        let mut game = Game::with_seed(30);
+        game.signed_numbers = true;
+
+        for game_type in [
+            GameType::Addition,
+            GameType::Subtraction,
+            GameType::Multiplication,
+            GameType::Division,
+            GameType::DivisionWithRemainder,
+            GameType::Chain,
+        ] {
+            game.current_type = game_type;
+            let mut negative = 0;
+            for _ in 0..100 {
+                let problem = game.generate_problem().unwrap();
+                let numbers = problem.expr.numbers();
+                negative += numbers.iter().filter(|n| **n < 0).count();
+                // negative operands after the first one are always in parentheses
+                for n in numbers.iter().skip(1).filter(|n| **n < 0) {
+                    assert!(
+                        problem.problem.contains(&format!("({})", n)),
+                        "{}",
+                        problem.problem
+                    );
+                }
+                assert_eq!(problem.expr.evaluate(), Some(problem.answer));
+            }
+            assert!(negative > 0, "{:?} never had a negative operand", game_type);
+        }
+    }
+
+    #[test]
+    fn test_rounds_have_no_trivial_or_repeated_problems() {
+        let game_types = [
+            GameType::Addition,
+            GameType::Subtraction,
+            GameType::Multiplication,
+            GameType::Division,
+            GameType::DivisionWithRemainder,
+            GameType::FractionAddition,
+            GameType::FractionSubtraction,
+            GameType::FractionMultiplication,
+            GameType::FractionDivision,
+            GameType::DecimalAddition,
+            GameType::DecimalMultiplication,
+            GameType::Chain,
+        ];
+
+        let mut game = Game::new();
+        for game_type in game_types {
+            game.current_type = game_type;
+            for seed in 0..20 {
+                game.reseed(seed);
+                let mut seen = HashSet::new();
+                for _ in 0..game.problems_per_round {
+                    let problem = game.generate_problem().unwrap();
+                    assert!(
+                        !quality::is_trivial(&problem.expr, &problem.answer),
+                        "{}",
+                        problem.problem
+                    );
+                    assert!(seen.insert(problem.problem.clone()), "{}", problem.problem);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_regrouping_constraints() {
+        let mut game = Game::with_seed(6);
+        game.current_difficulty = GameDifficulty::Medium;
+        for (game_type, regrouping) in [
+            (GameType::Addition, Regrouping::Required),
+            (GameType::Addition, Regrouping::Forbidden),
+            (GameType::Subtraction, Regrouping::Required),
+            (GameType::Subtraction, Regrouping::Forbidden),
+        ] {
+            game.current_type = game_type;
+            game.regrouping = regrouping;
+            for _ in 0..50 {
+                // every round is short enough to never run out of problems
+                game.reseed(game.seed() + 1);
+                for _ in 0..game.problems_per_round {
+                    let problem = game.generate_problem().unwrap();
+                    let (a, b) = (problem.operand1, problem.operand2);
+                    let regroups = match game_type {
+                        GameType::Addition => quality::needs_carry(a, b),
+                        _ => quality::needs_borrow(a, b),
+                    };
+                    assert_eq!(
+                        regroups,
+                        regrouping == Regrouping::Required,
+                        "{}",
+                        problem.problem
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_regrouping_that_cant_be_met() {
+        // single digit subtractions never borrow
+        let mut game = Game::with_seed(6);
+        game.current_type = GameType::Subtraction;
+        game.regrouping = Regrouping::Required;
+        assert_eq!(game.generate_problem(), None);
+        assert_eq!(game.current_problem, None);
+        game.regrouping = Regrouping::Any;
+        assert!(game.generate_problem().is_some());
+
+        // with negative numbers the carries are those of the signed sum, -3 + 7 is 7 - 3
+        game.signed_numbers = true;
+        game.current_difficulty = GameDifficulty::Medium;
+        game.current_type = GameType::Addition;
+        game.regrouping = Regrouping::Forbidden;
+        for _ in 0..20 {
+            let problem = game.generate_problem().unwrap();
+            let (a, b) = (problem.operand1, problem.operand2);
+            let column_work = complexity::signed_sum(a, b);
+            assert_eq!(
+                column_work.carries + column_work.borrows,
+                0,
+                "{}",
+                problem.problem
+            );
+        }
+    }
+
+    #[test]
+    fn test_problems_stay_in_complexity_band() {
+        let mut game = Game::with_seed(40);
+        for game_type in [
+            GameType::Addition,
+            GameType::Subtraction,
+            GameType::Multiplication,
+            GameType::Division,
+            GameType::FractionAddition,
+            GameType::DecimalAddition,
+        ] {
+            game.current_type = game_type;
+            for difficulty in [
+                GameDifficulty::Medium,
+                GameDifficulty::Hard,
+                GameDifficulty::Mastery,
+            ] {
+                game.current_difficulty = difficulty;
+                let band = game.profile(difficulty).complexity;
+                game.reseed(game.seed() + 1);
+                for _ in 0..game.problems_per_round {
+                    let problem = game.generate_problem().unwrap();
+                    assert!(
+                        band.contains(&problem.complexity),
+                        "{} at {:?}: {:?}",
+                        problem.problem,
+                        difficulty,
+                        problem.complexity
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_missing_operand_problems() {
+        let mut game = Game::with_seed(14);
+        game.missing_operands = true;
+
+        for game_type in [
+            GameType::Addition,
+            GameType::Subtraction,
+            GameType::Multiplication,
+            GameType::Division,
+            GameType::DivisionWithRemainder,
+            GameType::FractionAddition,
+            GameType::FractionDivision,
+            GameType::DecimalMultiplication,
+            GameType::Chain,
+        ] {
+            game.current_type = game_type;
+            let (mut hidden_operands, mut hidden_results) = (0, 0);
+            for _ in 0..60 {
+                let problem = game.generate_problem().unwrap();
+                let result = problem.expr.evaluate().unwrap();
+                match problem.problem.split_once(" = ") {
+                    Some((left, right)) => {
+                        hidden_operands += 1;
+                        assert_eq!(left.matches('?').count(), 1, "{}", problem.problem);
+                        assert_eq!(right, result.to_string());
+                    }
+                    None => {
+                        hidden_results += 1;
+                        assert_eq!(problem.answer, result);
+                    }
+                }
+            }
+            assert!(hidden_operands > hidden_results, "{:?}", game_type);
+            assert!(hidden_results > 0, "{:?}", game_type);
+        }
+    }
+
+    #[test]
+    fn test_hidden_operand_answer() {
+        let mut game = Game::with_mock_rng(|_, _| (7, 6));
+        game.current_type = GameType::Multiplication;
+        let mut problem = game.generate_problem().unwrap().hide_operand(0);
+        assert_eq!(problem.problem, "? * 6 = 42");
+        assert!(problem.check_answer(&Answer::Integer(7)));
+
+        // the complexity is the one of finding the unknown, ? + 6 = 13 is 13 - 6
+        game.current_type = GameType::Addition;
+        let sum = game.generate_problem().unwrap();
+        let problem = sum.clone().hide_operand(0);
+        assert_eq!(problem.problem, "? + 6 = 13");
+        assert_ne!(problem.complexity, sum.complexity);
+        let difference = Expr::binary(BinaryOp::Sub, Expr::number(13), Expr::number(6));
+        assert_eq!(problem.complexity, Complexity::of(&difference));
+
+        game.current_type = GameType::DivisionWithRemainder;
+        let problem = game.generate_problem().unwrap().hide_operand(1);
+        assert_eq!(problem.problem, "49 ÷ ? = 8 r 1");
+        assert_eq!(problem.answer, Answer::Integer(6));
+    }
+
+    #[test]
+    fn test_focus_drills() {
+        let mut game = Game::with_seed(15);
+        game.current_type = GameType::Multiplication;
+        game.focus = Some(Focus::Tables(vec![7, 8]));
+        for _ in 0..100 {
+            let problem = game.generate_problem().unwrap();
+            let (a, b) = (problem.operand1, problem.operand2);
+            assert!(
+                [7, 8].contains(&a) || [7, 8].contains(&b),
+                "{}",
+                problem.problem
+            );
+        }
+
+        let family = Focus::FactFamily(6, 7);
+        game.focus = Some(family.clone());
+        let mut seen = HashSet::new();
+        for seed in 0..10 {
+            game.reseed(seed);
+            for _ in 0..4 {
+                seen.insert(game.generate_problem().unwrap().problem);
+            }
+        }
+        let expected: HashSet<String> = ["6 * 7", "7 * 6", "42 / 6", "42 / 7"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(seen, expected);
+
+        // a drill has its own progress, the plain level doesn't move
+        let answer = game.current_problem.clone().unwrap().answer;
+        assert!(game.check_answer(&answer));
+        assert_eq!(game.focus_score(GameType::Multiplication, &family), 1);
+        assert_eq!(
+            game.focus_score(GameType::Multiplication, &Focus::Squares),
+            0
+        );
+        assert_eq!(
+            game.level_score(GameType::Multiplication, GameDifficulty::Basic),
+            0
+        );
+
+        // a round of a four fact family repeats them
+        game.reseed(3);
+        let round: Vec<Problem> = (0..game.problems_per_round)
+            .map(|_| game.generate_problem().unwrap())
+            .collect();
+        assert!(round.iter().all(|problem| problem.focused));
+        assert!(round
+            .iter()
+            .all(|problem| expected.contains(&problem.problem)));
+
+        // fractions are drawn from the difficulty and count for the level
+        game.current_type = GameType::FractionAddition;
+        let problem = game.generate_problem().unwrap();
+        assert!(!problem.focused);
+        assert!(game.check_answer(&problem.answer));
+        assert_eq!(game.focus_score(GameType::FractionAddition, &family), 0);
+        assert_eq!(
+            game.level_score(GameType::FractionAddition, GameDifficulty::Basic),
+            1
+        );
+    }
+
+    #[test]
+    fn test_deck_mode_deals_every_fact_across_rounds() {
+        let mut game = Game::with_seed(16);
+        game.deck_mode = true;
+        game.current_type = GameType::Multiplication;
+
+        let mut seen = HashSet::new();
+        for round in 0..9 {
+            game.reseed(round);
+            for _ in 0..game.problems_per_round - 1 {
+                let problem = game.generate_problem().unwrap();
+                assert!(game.check_answer(&problem.answer));
+                seen.insert(problem.problem);
+            }
+        }
+        assert_eq!(seen.len(), 81);
+        assert_eq!(game.deck_progress(), Some((81, 81)));
+
+        // a problem generated over another one doesn't use up its fact
+        let dealt = game.generate_problem().unwrap();
+        assert_eq!(game.generate_problem().unwrap().problem, dealt.problem);
+        assert_eq!(game.deck_progress(), Some((0, 81)));
+
+        // the position survives a restart through the settings
+        assert!(game.check_answer(&dealt.answer));
+        let mut restored = Game::with_seed(1);
+        restored.current_type = GameType::Multiplication;
+        crate::settings::Settings::from_game(&game).apply(&mut restored);
+        assert_eq!(restored.deck_progress(), Some((1, 81)));
+
+        // a focus drill doesn't deal from the deck, so there is no progress to show
+        restored.focus = Some(Focus::Tables(vec![7]));
+        assert_eq!(restored.deck_progress(), None);
+
+        // no deck for levels with too many facts
+        game.current_difficulty = GameDifficulty::Hard;
+        assert_eq!(game.deck_progress(), None);
+    }
+
+    #[test]
+    fn test_review_asks_due_facts() {
+        let mut game = Game::with_mock_rng(|_, _| (7, 8));
+        game.current_type = GameType::Multiplication;
+        game.generate_problem();
+
+        // typing the first digit of 56 is not a miss yet, 54 is
+        assert!(!game.check_answer(&Answer::Integer(5)));
+        assert!(!game.check_answer(&Answer::Integer(54)));
+        assert!(!game.check_answer(&Answer::Integer(55)));
+        assert!(game.check_answer(&Answer::Integer(56)));
+        let fact = Fact::of(game.current_problem.as_ref().unwrap()).unwrap();
+        let card = game.schedule.card(&fact).unwrap();
+        assert_eq!((card.reviews, card.lapses), (1, 1));
+        assert_eq!(game.due_facts(), 0);
+
+        let due = Fact {
+            game_type: GameType::Subtraction,
+            operand1: 12,
+            operand2: 5,
+        };
+        let long_ago = chrono::Utc::now() - chrono::Duration::days(1);
+        game.schedule
+            .record(due, false, chrono::Duration::seconds(3), long_ago);
+        assert_eq!(game.due_facts(), 1);
+
+        game.review = true;
+        assert_eq!(game.generate_problem().unwrap().problem, "12 - 5");
+        assert!(game.check_answer(&Answer::Integer(7)));
+        assert_eq!(game.due_facts(), 0);
+        // nothing is due, the round goes on with the game type
+        assert_eq!(
+            game.generate_problem().unwrap().game_type,
+            GameType::Multiplication
+        );
+    }
+
+    #[test]
+    fn test_adaptive_levels() {
+        let mut game = Game::with_seed(3);
+        game.current_type = GameType::Multiplication;
+        assert_eq!(game.skill_level(), None);
+
+        game.adaptive = true;
+        assert_eq!(game.skill_level(), Some(1));
+        for _ in 0..2 {
+            let answer = game.generate_problem().unwrap().answer;
+            assert!(game.check_answer(&answer));
+        }
+        assert_eq!(game.skill_level(), Some(2));
+
+        // every answer at level 3 uses two digit operands
+        for _ in 0..2 {
+            let answer = game.generate_problem().unwrap().answer;
+            assert!(game.check_answer(&answer));
+        }
+        assert_eq!(game.skill_level(), Some(3));
+        let problem = game.generate_problem().unwrap();
+        assert!((10..=99).contains(&problem.operand1), "{}", problem.problem);
+
+        // a miss steps back down, the other game types keep their own level
+        assert!(!game.check_answer(&Answer::Integer(-99999)));
+        assert_eq!(game.skill_level(), Some(2));
+        game.current_type = GameType::Addition;
+        assert_eq!(game.skill_level(), Some(1));
+    }
+
+    #[test]
+    fn test_answers_update_the_rating() {
+        let mut game = Game::with_seed(8);
+        game.current_type = GameType::Subtraction;
+        let answer = game.generate_problem().unwrap().answer;
+        assert!(game.check_answer(&answer));
+        let rating = game.rating();
+        assert!(rating > crate::rating::INITIAL_RATING);
+
+        // a miss counts once, the right answer after it doesn't gain anything
+        let answer = game.generate_problem().unwrap().answer;
+        assert!(!game.check_answer(&Answer::Integer(-99999)));
+        assert!(!game.check_answer(&Answer::Integer(-99999)));
+        let after_miss = game.rating();
+        assert!(after_miss < rating);
+        assert!(game.check_answer(&answer));
+        assert_eq!(game.rating(), after_miss);
+
+        game.current_difficulty = GameDifficulty::Hard;
+        assert_eq!(game.rating(), crate::rating::INITIAL_RATING);
+    }
+
+    #[test]
+    fn test_placement_places_every_operation() {
+        let mut game = Game::with_seed(12);
+        game.current_type = GameType::Division;
+        game.focus = Some(Focus::Squares);
+        game.start_placement();
+        assert_eq!(game.focus, None);
+        assert_eq!(
+            (game.current_type, game.current_difficulty),
+            (GameType::Addition, GameDifficulty::Hard)
+        );
+
+        // every answer is right and quick
+        let mut problems = 0;
+        while game.placement.is_some() {
+            let answer = game.current_problem.clone().unwrap().answer;
+            assert!(game.check_answer(&answer));
+            assert!(!game.is_round_completed());
+            problems += 1;
+            if game.placement.is_some() {
+                game.generate_problem();
+            }
+        }
+        assert_eq!(problems, 28);
+        assert!(game.game_state == GameState::NotStarted);
+        assert_eq!(
+            (game.current_type, game.current_difficulty),
+            (GameType::Division, GameDifficulty::Mastery)
+        );
+        assert_eq!(game.placements.len(), 14);
+        assert_eq!(game.placements[&GameType::Chain].correct, 2);
+        game.adaptive = true;
+        assert_eq!(game.skill_level(), Some(7));
+    }
+
+    #[test]
+    fn test_round_after_placement_scores_from_zero() {
+        let mut game = Game::with_seed(21);
+        game.start_placement();
+        while game.placement.is_some() {
+            let answer = game.current_problem.clone().unwrap().answer;
+            assert!(game.check_answer(&answer));
+            if game.placement.is_some() {
+                game.generate_problem();
+            }
+        }
+        assert!(game.score > 0);
+
+        game.start_round(21);
+        assert_eq!((game.score, game.current_round_completed_problems), (0, 0));
+        for _ in 0..3 {
+            let answer = game.current_problem.clone().unwrap().answer;
+            assert!(game.check_answer(&answer));
+            game.generate_problem();
+        }
+        assert_eq!(game.round_score.total(), game.score);
+        assert_eq!(game.current_round_completed_problems, 3);
+    }
+
+    #[test]
+    fn test_placement_returns_to_the_level_of_before() {
+        let mut game = Game::with_seed(13);
+        game.current_type = GameType::Mixed;
+        game.current_difficulty = GameDifficulty::Medium;
+        game.start_placement();
+        while game.placement.is_some() {
+            let answer = game.current_problem.clone().unwrap().answer;
+            assert!(game.check_answer(&answer));
+            if game.placement.is_some() {
+                game.generate_problem();
+            }
+        }
+        assert_eq!(
+            (game.current_type, game.current_difficulty),
+            (GameType::Mixed, GameDifficulty::Medium)
+        );
+
+        // a cancelled test keeps nothing
+        let mut game = Game::with_seed(13);
+        game.current_type = GameType::Division;
+        game.start_placement();
+        let answer = game.current_problem.clone().unwrap().answer;
+        assert!(game.check_answer(&answer));
+        game.generate_problem();
+        game.cancel_placement();
+        assert!(game.placement.is_none());
+        assert!(game.placements.is_empty());
+        assert!(game.game_state == GameState::NotStarted);
+        assert_eq!(
+            (game.current_type, game.current_difficulty),
+            (GameType::Division, GameDifficulty::Basic)
+        );
+        assert_eq!(game.current_problem.unwrap().game_type, GameType::Division);
+    }
+
+    #[test]
+    fn test_sprint_round_ends_with_the_clock() {
+        let mut game = Game::with_seed(4);
+        game.sprint_seconds = Some(60);
+        assert_eq!(game.time_left(chrono::Utc::now()), None);
+
+        game.start_round(4);
+        let started = game.round_started_at.unwrap();
+        for _ in 0..game.problems_per_round + 5 {
+            let answer = game.current_problem.clone().unwrap().answer;
+            assert!(game.check_answer(&answer));
+            assert!(!game.is_round_completed());
+            game.generate_problem();
+        }
+
+        game.check_time(started + chrono::Duration::seconds(59));
+        assert!(game.game_state == GameState::Playing);
+        assert_eq!(
+            game.time_left(started + chrono::Duration::seconds(45)),
+            Some(chrono::Duration::seconds(15))
+        );
+        game.check_time(started + chrono::Duration::seconds(61));
+        assert!(game.game_state == GameState::TimeUp);
+        assert_eq!(game.score, 15);
+    }
+
+    #[test]
+    fn test_round_history() {
+        let mut game = Game::with_seed(6);
+        game.start_round(6);
+        assert_eq!(game.round_stats(), None);
+        for _ in 0..3 {
+            let answer = game.current_problem.clone().unwrap().answer;
+            assert!(game.check_answer(&answer));
+            game.generate_problem();
+        }
+        assert_eq!(game.round_history.len(), 3);
+        assert!(game.round_history.iter().all(|p| p.latency().is_some()));
+        let stats = game.round_stats().unwrap();
+        assert!(stats.fastest <= stats.median && stats.median <= stats.slowest);
+
+        // the next round starts a new history
+        game.start_round(7);
+        assert!(game.round_history.is_empty());
+    }
+
+    #[test]
+    fn test_attempts_and_accuracy() {
+        let mut game = Game::with_mock_rng(|_, _| (7, 8));
+        game.current_type = GameType::Multiplication;
+        game.generate_problem();
+
+        // typing 5 on the way to 56 is not an attempt, the sign of -5 is not a digit either
+        assert!(!game.check_answer(&Answer::Integer(5)));
+        assert!(!game.check_answer(&Answer::Integer(-5)));
+        assert!(!game.check_answer(&Answer::Integer(54)));
+        assert!(game.check_answer(&Answer::Integer(56)));
+        let attempts = &game.current_problem.as_ref().unwrap().attempts;
+        assert_eq!(
+            attempts
+                .iter()
+                .map(|a| (a.value.clone(), a.correct))
+                .collect::<Vec<_>>(),
+            vec![(Answer::Integer(54), false), (Answer::Integer(56), true)]
+        );
+        assert_eq!(game.round_accuracy(), (1, 2));
+
+        game.generate_problem();
+        assert!(!game.check_answer(&Answer::Integer(99)));
+        assert_eq!(game.round_accuracy(), (1, 3));
+    }
+
+    #[test]
+    fn test_mistakes_cost_points_and_lives() {
+        let mut game = Game::with_mock_rng(|_, _| (7, 8));
+        game.current_type = GameType::Multiplication;
+        game.scoring.mistake_penalty = 2;
+        game.lives = Some(2);
+        // a new round reseeds, the mock keeps asking 7 * 8 instead
+        game.generate_problem();
+        game.game_state = GameState::Playing;
+        game.lives_left = 2;
+
+        game.score = 3;
+        assert!(!game.check_answer(&Answer::Integer(54)));
+        assert_eq!((game.score, game.lives_left), (1, 1));
+        assert!(game.game_state == GameState::Playing);
+        assert!(!game.check_answer(&Answer::Integer(55)));
+        assert_eq!((game.score, game.lives_left), (0, 0));
+        assert!(game.game_state == GameState::OutOfLives);
+    }
+
+    #[test]
+    fn test_fraction_attempts() {
+        let mut game = Game::with_mock_rng(|_, _| (3, 8));
+        game.current_type = GameType::FractionAddition;
+        game.fraction_policy = FractionPolicy::LowestTerms;
+        game.generate_problem();
+        let expected = game.current_problem.as_ref().unwrap().answer.clone();
+        let Answer::Fraction(value) = expected else {
+            panic!("fraction problems have fraction answers");
+        };
+        // 3/8 + 3/8 = 3/4, 6/8 is the right value in the wrong form
+        assert_eq!(value, Rational::new(3, 4).unwrap());
+        game.check_fraction_answer(&written(0, 6, 8));
+        game.check_fraction_answer(&written(0, 1, 4));
+        assert!(game.check_fraction_answer(&written(0, 3, 4)).is_correct());
+        let attempts = &game.current_problem.as_ref().unwrap().attempts;
+        assert_eq!(
+            attempts.iter().map(|a| a.correct).collect::<Vec<_>>(),
+            vec![false, true]
+        );
+    }
+
+    #[test]
+    fn test_scoring_policy() {
+        let mut game = Game::with_seed(2);
+        game.current_difficulty = GameDifficulty::Medium;
+        game.scoring = ScoringPolicy {
+            points: crate::scoring::Points::Flat,
+            streak_multiplier: true,
+            difficulty_multiplier: true,
+            mistake_penalty: 3,
+        };
+        game.start_round(2);
+        for _ in 0..5 {
+            let answer = game.current_problem.clone().unwrap().answer;
+            assert!(game.check_answer(&answer));
+            game.generate_problem();
+        }
+        // four answers at twice the points for Medium, the fifth doubles again for the streak
+        assert_eq!(game.score, 4 * 2 + 4);
+        assert!(!game.check_answer(&Answer::Integer(-99999)));
+        assert_eq!(game.score, 9);
+        assert_eq!(
+            game.round_score,
+            ScoreBreakdown {
+                answers: 5,
+                speed: 0,
+                streak: 1,
+                difficulty: 6,
+                penalties: 3,
+            }
+        );
+        assert_eq!(game.round_score.total(), game.score);
+    }
+
+    #[test]
+    fn test_submitted_answers() {
+        let mut game = Game::with_mock_rng(|_, _| (7, 8));
+        game.current_type = GameType::Multiplication;
+        game.generate_problem();
+
+        // a submitted answer counts even when it is short
+        assert!(!game.submit_answer(&Answer::Integer(5)));
+        assert_eq!(game.wrong_attempts(), 1);
+        assert!(!game.check_answer(&Answer::Integer(5)));
+        assert_eq!(game.wrong_attempts(), 1);
+        assert!(game.submit_answer(&Answer::Integer(56)));
+
+        game.current_type = GameType::FractionAddition;
+        game.generate_problem();
+        // 7/8 + 7/8 = 7/4, a one digit denominator is complete already
+        assert_eq!(
+            game.check_fraction_answer(&written(0, 1, 1)),
+            Verdict::Incorrect
+        );
+        assert_eq!(game.wrong_attempts(), 1);
+        assert_eq!(
+            game.submit_fraction_answer(&written(0, 14, 8)),
+            Verdict::Correct
+        );
+        assert_eq!(count_digits("-17 r 3"), 3);
+        assert_eq!(count_digits(&Answer::Integer(-56).to_string()), 2);
+    }
+
+    #[test]
+    fn test_parse_minus_signs() {
+        assert_eq!(Answer::parse_integer("-12"), Some(Answer::Integer(-12)));
+        assert_eq!(
+            Answer::parse_integer("\u{2212}12"),
+            Some(Answer::Integer(-12))
+        );
+        assert_eq!(
+            Answer::parse_integer(" \u{FF0D}3 "),
+            Some(Answer::Integer(-3))
+        );
+        assert_eq!(Answer::parse_integer("--3"), None);
+        assert_eq!(
+            Answer::parse_decimal("\u{2212}.5"),
+            Some(Answer::Decimal("-0.5".parse().unwrap()))
+        );
+        assert_eq!(
+            WrittenFraction::parse("", "\u{2212}1", "2").and_then(|w| w.value()),
+            Rational::new(-1, 2)
+        );
+    }
 }
@@ -1,46 +1,304 @@
 use eframe::egui;
+mod adaptive;
+mod chain;
+mod complexity;
+mod decimal;
+mod deck;
+mod difficulty;
+mod expr;
+mod focus;
 mod game;
-use crate::game::{Answer, Game, GameDifficulty, GameState, GameType, Problem};
+mod placement;
+mod quality;
+mod rating;
+mod rational;
+mod review;
+mod rng;
+mod scoring;
+mod settings;
+mod stats;
+use crate::difficulty::{OperandRange, MAX_DIGITS, MAX_OPERAND};
+use crate::focus::Focus;
+use crate::game::{
+    count_digits, Answer, FractionPolicy, Game, GameState, GameType, SubmitMode, WrittenFraction,
+    MAX_DECIMAL_PLACES, SPRINT_LENGTHS,
+};
+use crate::quality::Regrouping;
+use crate::scoring::{Points, ScoreBreakdown};
+use crate::settings::Settings;
 
 fn main() -> eframe::Result<()> {
     let native_options = eframe::NativeOptions::default();
     eframe::run_native(
         "Speed Math",
         native_options,
-        Box::new(|_| Ok(Box::new(App::default()))),
+        Box::new(|cc| Ok(Box::new(App::new(cc)))),
     )
 }
 pub struct App {
     game: Game,
     current_answer: String,
+    current_whole: String,
     current_numerator: String,
     current_denominator: String,
+    feedback: Option<&'static str>,
+    seed_input: String,
 }
 
 impl Default for App {
     fn default() -> Self {
-        let mut game = Game::new();
+        // SPEED_MATH_SEED replays the exact problems of a reported or shared round
+        let mut game = match std::env::var("SPEED_MATH_SEED").map(|s| s.parse::<u64>()) {
+            Ok(Ok(seed)) => Game::with_seed(seed),
+            _ => Game::new(),
+        };
         let _ = game.generate_problem();
         Self {
+            seed_input: game.seed().to_string(),
             game,
             current_answer: "".to_string(),
+            current_whole: String::new(),
             current_numerator: String::new(), // TODO: consider making it optional and use a struct for fraction
             current_denominator: String::new(),
+            feedback: None,
         }
     }
 }
 
 impl App {
     /// Called once before the first frame.
-    pub fn new(_: &eframe::CreationContext<'_>) -> Self {
-        Default::default()
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self::default();
+        if let Some(settings) = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Settings>(storage, eframe::APP_KEY))
+        {
+            settings.apply(&mut app.game);
+            app.game.generate_problem();
+        }
+        app
+    }
+
+    // text_answer_ui is the input of whole number, decimal and remainder answers, the submit
+    // mode decides when it is checked
+    fn text_answer_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        expected: &Answer,
+        parse: fn(&str) -> Option<Answer>,
+        hint: &str,
+    ) {
+        let response = ui.add(egui::TextEdit::singleline(&mut self.current_answer).hint_text(hint));
+        let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if response.changed() {
+            self.feedback = None;
+        }
+
+        let submitted = match self.game.submit_mode {
+            SubmitMode::ExactMatch => false,
+            SubmitMode::Enter => enter,
+            SubmitMode::DigitCount => {
+                response.changed()
+                    && count_digits(&self.current_answer) >= count_digits(&expected.to_string())
+            }
+        };
+        let typed = self.game.submit_mode == SubmitMode::ExactMatch && response.changed();
+        if !submitted && !typed {
+            return;
+        }
+        let Some(user_answer) = parse(&self.current_answer) else {
+            return;
+        };
+
+        let wrong_before = self.game.wrong_attempts();
+        let correct = if submitted {
+            self.game.submit_answer(&user_answer)
+        } else {
+            self.game.check_answer(&user_answer)
+        };
+        self.after_answer(correct, wrong_before);
+        // Enter takes the focus away, the next answer goes in the same field
+        if enter {
+            response.request_focus();
+        }
+    }
+
+    // fraction_answer_ui is the input of fraction answers, the denominator's digits count in
+    // digit count mode since it is typed last
+    fn fraction_answer_ui(&mut self, ui: &mut egui::Ui, expected: &Answer) {
+        let (mut changed, mut enter) = (false, false);
+        ui.horizontal(|ui| {
+            let mut responses = Vec::new();
+            if self.game.fraction_policy == FractionPolicy::MixedNumber {
+                responses.push(
+                    ui.add(egui::TextEdit::singleline(&mut self.current_whole).desired_width(40.0)),
+                );
+            }
+            responses.push(ui.text_edit_singleline(&mut self.current_numerator));
+            ui.label("/");
+            responses.push(ui.text_edit_singleline(&mut self.current_denominator));
+
+            for response in responses {
+                changed |= response.changed();
+                enter |= response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            }
+        });
+        if changed {
+            self.feedback = None;
+        }
+
+        let denominator_digits = match expected {
+            Answer::Fraction(value) => count_digits(&value.denominator().to_string()),
+            _ => 1,
+        };
+        let submitted = match self.game.submit_mode {
+            SubmitMode::ExactMatch => false,
+            SubmitMode::Enter => enter,
+            SubmitMode::DigitCount => {
+                changed && count_digits(&self.current_denominator) >= denominator_digits
+            }
+        };
+        let typed = self.game.submit_mode == SubmitMode::ExactMatch && changed;
+        if !submitted && !typed {
+            return;
+        }
+        // a zero denominator is not a fraction
+        let Some(written) = WrittenFraction::parse(
+            &self.current_whole,
+            &self.current_numerator,
+            &self.current_denominator,
+        ) else {
+            return;
+        };
+
+        let wrong_before = self.game.wrong_attempts();
+        let verdict = if submitted {
+            self.game.submit_fraction_answer(&written)
+        } else {
+            self.game.check_fraction_answer(&written)
+        };
+        match verdict.feedback() {
+            // the right value in another form gets a hint instead
+            Some(hint) => self.feedback = Some(hint),
+            None => self.after_answer(verdict.is_correct(), wrong_before),
+        }
     }
+
+    // after_answer is the same in every submit mode: a right answer moves on to the next
+    // problem, a wrong one clears the input for another try
+    fn after_answer(&mut self, correct: bool, wrong_before: usize) {
+        if correct {
+            self.clear_inputs();
+            self.feedback = Some("Correct!");
+            if self.game.is_round_completed() {
+                self.game.game_state = GameState::RoundComplete;
+            } else {
+                self.game.generate_problem();
+            }
+        } else if self.game.wrong_attempts() > wrong_before {
+            self.clear_inputs();
+            self.feedback = Some("Not quite, try again");
+        }
+    }
+
+    fn clear_inputs(&mut self) {
+        self.current_answer.clear();
+        self.current_whole.clear();
+        self.current_numerator.clear();
+        self.current_denominator.clear();
+    }
+}
+
+// focus drills are a handful of facts, so they take the threshold of the first level
+const FOCUS_STAR_THRESHOLD: u32 = 100;
+
+// format_time shows seconds as minutes and seconds, 2:05
+fn format_time(seconds: u32) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// format_seconds shows an answer time with a tenth of a second, 3.4 s
+fn format_seconds(duration: chrono::Duration) -> String {
+    format!("{:.1} s", duration.num_milliseconds() as f64 / 1000.0)
+}
+
+// score_breakdown_ui lists where the points of the round came from, the parts the scoring
+// policy didn't use are left out
+fn score_breakdown_ui(ui: &mut egui::Ui, score: &ScoreBreakdown) {
+    let parts = [
+        ("Right answers", score.answers),
+        ("Speed points", score.speed),
+        ("Streak bonus", score.streak),
+        ("Difficulty bonus", score.difficulty),
+        ("Mistakes", -score.penalties),
+    ];
+    egui::Grid::new("score_breakdown").show(ui, |ui| {
+        for (name, points) in parts.into_iter().filter(|(_, points)| *points != 0) {
+            ui.label(name);
+            ui.label(format!("{:+}", points));
+            ui.end_row();
+        }
+        ui.label("Total");
+        ui.label(score.total().to_string());
+        ui.end_row();
+    });
+}
+
+// draw_stars draws 10 stars, the first golden ones are awarded
+fn draw_stars(ui: &mut egui::Ui, golden: u32) {
+    for star in 0..10 {
+        let star_color = if star < golden {
+            egui::Color32::GOLD
+        } else {
+            egui::Color32::GRAY
+        };
+
+        ui.label(egui::RichText::new("★").color(star_color).size(16.0));
+    }
+}
+
+// operand_range_ui edits one operand of a profile either by digit count or by min and max
+fn operand_range_ui(ui: &mut egui::Ui, label: &str, range: &mut OperandRange) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label(label);
+        let mut digits = range.digit_count();
+        if ui
+            .add(
+                egui::DragValue::new(&mut digits)
+                    .range(1..=MAX_DIGITS)
+                    .suffix(" digits"),
+            )
+            .changed()
+        {
+            *range = OperandRange::digits(digits);
+            changed = true;
+        }
+    });
+    ui.horizontal(|ui| {
+        let (mut min, mut max) = (range.min, range.max);
+        ui.label("from");
+        changed |= ui
+            .add(egui::DragValue::new(&mut min).range(1..=MAX_OPERAND))
+            .changed();
+        ui.label("to");
+        changed |= ui
+            .add(egui::DragValue::new(&mut max).range(1..=MAX_OPERAND))
+            .changed();
+        if (min, max) != (range.min, range.max) {
+            *range = OperandRange::new(min, max);
+        }
+    });
+    changed
 }
 
 impl eframe::App for App {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, &Settings::from_game(&self.game));
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
-
             egui::menu::bar(ui, |ui| {
                 egui::widgets::global_theme_preference_buttons(ui);
             });
@@ -64,6 +322,10 @@ impl eframe::App for App {
                         self.game.current_type = GameType::Division;
                         self.game.generate_problem();
                     }
+                    if ui.button("Division with Remainder").clicked() {
+                        self.game.current_type = GameType::DivisionWithRemainder;
+                        self.game.generate_problem();
+                    }
                     if ui.button("Fraction Addition").clicked() {
                         self.game.current_type = GameType::FractionAddition;
                         self.game.generate_problem();
@@ -72,25 +334,77 @@ impl eframe::App for App {
                         self.game.current_type = GameType::FractionSubtraction;
                         self.game.generate_problem();
                     }
+                    if ui.button("Fraction Multiplication").clicked() {
+                        self.game.current_type = GameType::FractionMultiplication;
+                        self.game.generate_problem();
+                    }
+                    if ui.button("Fraction Division").clicked() {
+                        self.game.current_type = GameType::FractionDivision;
+                        self.game.generate_problem();
+                    }
+                    if ui.button("Decimal Addition").clicked() {
+                        self.game.current_type = GameType::DecimalAddition;
+                        self.game.generate_problem();
+                    }
+                    if ui.button("Decimal Subtraction").clicked() {
+                        self.game.current_type = GameType::DecimalSubtraction;
+                        self.game.generate_problem();
+                    }
+                    if ui.button("Decimal Multiplication").clicked() {
+                        self.game.current_type = GameType::DecimalMultiplication;
+                        self.game.generate_problem();
+                    }
+                    if ui.button("Decimal Division").clicked() {
+                        self.game.current_type = GameType::DecimalDivision;
+                        self.game.generate_problem();
+                    }
+                    if ui.button("Chain").clicked() {
+                        self.game.current_type = GameType::Chain;
+                        self.game.generate_problem();
+                    }
+                    if ui.button("Mixed").clicked() {
+                        self.game.current_type = GameType::Mixed;
+                        self.game.generate_problem();
+                    }
                 });
 
                 // Difficulty selection
                 ui.menu_button("Difficulty", |ui| {
-                    if ui.button("Basic").clicked() {
-                        self.game.current_difficulty = GameDifficulty::Basic;
-                        self.game.generate_problem();
+                    for profile in self.game.profiles() {
+                        if ui.button(&profile.name).clicked() {
+                            self.game.current_difficulty = profile.difficulty;
+                            self.game.generate_problem();
+                        }
                     }
-                    if ui.button("Medium").clicked() {
-                        self.game.current_difficulty = GameDifficulty::Medium;
+                });
+
+                // due facts come back from the schedule before the usual problems
+                ui.menu_button("Review", |ui| {
+                    let due = self.game.due_facts();
+                    if ui.button(format!("Review due facts ({})", due)).clicked() {
+                        self.game.review = true;
                         self.game.generate_problem();
+                        ui.close_menu();
                     }
-                    if ui.button("Hard").clicked() {
-                        self.game.current_difficulty = GameDifficulty::Hard;
+                    if self.game.review && ui.button("Stop reviewing").clicked() {
+                        self.game.review = false;
                         self.game.generate_problem();
+                        ui.close_menu();
                     }
-                    if ui.button("Mastery").clicked() {
-                        self.game.current_difficulty = GameDifficulty::Mastery;
-                        self.game.generate_problem();
+                });
+
+                // the placement test picks a starting level for every operation
+                ui.menu_button("Placement", |ui| {
+                    if ui.button("Take placement test").clicked() {
+                        self.current_answer.clear();
+                        self.game.start_placement();
+                        ui.close_menu();
+                    }
+                    if self.game.placement.is_some() && ui.button("Cancel placement test").clicked()
+                    {
+                        self.current_answer.clear();
+                        self.game.cancel_placement();
+                        ui.close_menu();
                     }
                 });
 
@@ -107,42 +421,199 @@ impl eframe::App for App {
                 ("Subtraction", GameType::Subtraction),
                 ("Multiplication", GameType::Multiplication),
                 ("Division", GameType::Division),
+                ("Division with Remainder", GameType::DivisionWithRemainder),
                 ("Fraction Addition", GameType::FractionAddition),
                 ("Fraction Subtraction", GameType::FractionSubtraction),
+                ("Fraction Multiplication", GameType::FractionMultiplication),
+                ("Fraction Division", GameType::FractionDivision),
+                ("Decimal Addition", GameType::DecimalAddition),
+                ("Decimal Subtraction", GameType::DecimalSubtraction),
+                ("Decimal Multiplication", GameType::DecimalMultiplication),
+                ("Decimal Division", GameType::DecimalDivision),
+                ("Chain", GameType::Chain),
+                ("Mixed", GameType::Mixed),
             ];
 
-            let difficulties = [
-                ("Basic", GameDifficulty::Basic, 100), // Score threshold for golden stars
-                ("Medium", GameDifficulty::Medium, 200),
-                ("Hard", GameDifficulty::Hard, 300),
-                ("Mastery", GameDifficulty::Mastery, 400),
-            ];
+            let profiles = self.game.profiles();
+
+            ui.horizontal(|ui| {
+                ui.label("Chain operands: ");
+                let response =
+                    ui.add(egui::DragValue::new(&mut self.game.chain_operands).range(2..=6));
+                if response.changed() && matches!(self.game.current_type, GameType::Chain) {
+                    self.game.generate_problem();
+                }
+            });
+
+            ui.collapsing("Mixed operations", |ui| {
+                // weight 0 leaves the operation out of mixed rounds
+                let mut changed = false;
+                for (game_type, weight) in self.game.mixed_operations.iter_mut() {
+                    let name = game_types
+                        .iter()
+                        .find(|(_, t)| t == game_type)
+                        .map_or("", |(name, _)| *name);
+                    ui.horizontal(|ui| {
+                        changed |= ui.add(egui::DragValue::new(weight).range(0..=10)).changed();
+                        ui.label(name);
+                    });
+                }
+                if changed && self.game.current_type == GameType::Mixed {
+                    self.game.generate_problem();
+                }
+            });
+
+            ui.collapsing("Focus drills", |ui| {
+                let mut changed = false;
+                let kinds = [
+                    ("Off", None),
+                    ("Times tables", Some(Focus::Tables(vec![7, 8]))),
+                    ("Squares", Some(Focus::Squares)),
+                    ("Fact family", Some(Focus::FactFamily(6, 7))),
+                ];
+                ui.horizontal(|ui| {
+                    for (name, kind) in kinds {
+                        let selected = match (&self.game.focus, &kind) {
+                            (Some(focus), Some(kind)) => {
+                                std::mem::discriminant(focus) == std::mem::discriminant(kind)
+                            }
+                            (focus, kind) => focus.is_none() && kind.is_none(),
+                        };
+                        if ui.selectable_label(selected, name).clicked() && !selected {
+                            self.game.focus = kind;
+                            changed = true;
+                        }
+                    }
+                });
+
+                match &mut self.game.focus {
+                    Some(Focus::Tables(tables)) => {
+                        ui.horizontal_wrapped(|ui| {
+                            for n in 2..=12 {
+                                let mut on = tables.contains(&n);
+                                if ui.checkbox(&mut on, n.to_string()).changed() {
+                                    tables.retain(|table| *table != n);
+                                    if on {
+                                        tables.push(n);
+                                        tables.sort();
+                                    }
+                                    changed = true;
+                                }
+                            }
+                        });
+                    }
+                    Some(Focus::FactFamily(x, y)) => {
+                        ui.horizontal(|ui| {
+                            changed |= ui.add(egui::DragValue::new(x).range(2..=99)).changed();
+                            ui.label("and");
+                            changed |= ui.add(egui::DragValue::new(y).range(2..=99)).changed();
+                        });
+                    }
+                    Some(Focus::Squares) | None => {}
+                }
+
+                if let Some(focus) = &self.game.focus {
+                    ui.horizontal(|ui| {
+                        let score = self.game.focus_score(self.game.current_type, focus);
+                        let golden = if score >= FOCUS_STAR_THRESHOLD { 10 } else { 0 };
+                        draw_stars(ui, golden);
+                    });
+                }
+                if changed {
+                    self.game.generate_problem();
+                }
+            });
+
+            ui.collapsing("Difficulty profiles", |ui| {
+                let mut changed = false;
+                let mut removed = None;
+                for profile in self.game.custom_profiles.iter_mut() {
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut profile.name);
+                        if ui.button("Delete").clicked() {
+                            removed = Some(profile.difficulty);
+                        }
+                    });
+                    let current = profile.difficulty == self.game.current_difficulty;
+                    changed |=
+                        operand_range_ui(ui, "First operand: ", &mut profile.operand1) && current;
+                    changed |=
+                        operand_range_ui(ui, "Second operand: ", &mut profile.operand2) && current;
+                    ui.horizontal(|ui| {
+                        // the share of the possible carries and partial products a problem uses
+                        let band = &mut profile.complexity;
+                        ui.label("Complexity from");
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut band.min).range(0..=band.max))
+                            .changed()
+                            && current;
+                        ui.label("to");
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut band.max)
+                                    .range(band.min..=100)
+                                    .suffix("%"),
+                            )
+                            .changed()
+                            && current;
+                    });
+                    ui.separator();
+                }
+                if let Some(difficulty) = removed {
+                    self.game.remove_profile(difficulty);
+                    changed = true;
+                }
+
+                // new profiles start from the ranges of the current difficulty
+                if ui.button("New profile").clicked() {
+                    let name = format!("Custom {}", self.game.custom_profiles.len() + 1);
+                    self.game.current_difficulty =
+                        self.game.add_profile(&name, self.game.current_difficulty);
+                    changed = true;
+                }
+                if changed {
+                    self.game.generate_problem();
+                }
+            });
+            ui.add_space(10.0);
 
             for (game_name, game_type) in game_types.iter() {
                 ui.collapsing(*game_name, |ui| {
-                    for (difficulty_name, difficulty, threshold) in difficulties.iter() {
+                    let placed = self.game.placements.get(game_type).copied();
+                    if let Some(result) = placed {
+                        ui.label(format!(
+                            "Placement: {} of {} right, {:.1} s each",
+                            result.correct, result.answered, result.mean_seconds
+                        ));
+                    }
+                    for profile in profiles.iter() {
                         ui.horizontal(|ui| {
-                            if ui.button(*difficulty_name).clicked() {
+                            if ui.button(&profile.name).clicked() {
+                                // picking a level ends a placement test, which would pick the
+                                // next level itself
+                                self.game.cancel_placement();
                                 self.game.current_type = *game_type;
-                                self.game.current_difficulty = *difficulty;
+                                self.game.current_difficulty = profile.difficulty;
+                                // picking a level leaves the focus drill
+                                self.game.focus = None;
                                 self.game.generate_problem();
                             }
 
+                            if placed.is_some_and(|result| result.difficulty == profile.difficulty)
+                            {
+                                ui.label("placed");
+                            }
+
                             // Add space between difficulty name and stars
                             ui.add_space(10.0);
 
-                            // Draw 10 stars
-                            //let score = self.session.get_score(game_type, difficulty);
-                            let score = 10u32;
-                            for _ in 0..10 {
-                                let star_color = if score >= *threshold {
-                                    egui::Color32::GOLD
-                                } else {
-                                    egui::Color32::GRAY
-                                };
-
-                                ui.label(egui::RichText::new("★").color(star_color).size(16.0));
-                            }
+                            // the stars follow the rating, so a level can lose them again
+                            let rating = self.game.ratings.player(*game_type, profile.difficulty);
+                            draw_stars(ui, rating::stars(rating));
+                            ui.label(format!("{:.0}", rating)).on_hover_text(format!(
+                                "{} solved",
+                                self.game.level_score(*game_type, profile.difficulty)
+                            ));
                         });
                     }
                 });
@@ -150,109 +621,282 @@ impl eframe::App for App {
             }
         });
 
+        // the clock of a sprint runs between answers too, so keep repainting while it runs
+        self.game.check_time(chrono::Utc::now());
+        let time_left = match self.game.game_state {
+            GameState::Playing => self.game.time_left(chrono::Utc::now()),
+            _ => None,
+        };
+        if time_left.is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Speed Math");
 
+            if let Some(time_left) = time_left {
+                // round up so the clock shows 0:00 only when the time is up
+                let seconds = (time_left.num_milliseconds() as u32).div_ceil(1000);
+                ui.heading(format!("Time left: {}", format_time(seconds)));
+            }
+
             ui.horizontal(|ui| {
                 ui.label("Score: ");
                 ui.label(self.game.score.to_string());
+                ui.add_space(10.0);
+                ui.label(format!("Rating: {:.0}", self.game.rating()));
             });
 
+            if let Some((dealt, facts)) = self.game.deck_progress() {
+                ui.label(format!("Deck: {} of {} facts", dealt, facts));
+            }
+
+            if let Some(level) = self.game.skill_level() {
+                ui.label(format!("Level: {} of {}", level, adaptive::MAX_LEVEL));
+            }
+
+            if let Some((placed, operations)) = self.game.placement_progress() {
+                ui.label(format!(
+                    "Placement test: {} of {} operations placed",
+                    placed, operations
+                ));
+            }
+
+            if self.game.review {
+                ui.label(format!("Reviewing: {} facts due", self.game.due_facts()));
+            }
+
             ui.add_space(20.0);
 
+            if self.game.lives.is_some() && self.game.game_state == GameState::Playing {
+                ui.label(format!(
+                    "Lives: {}",
+                    "♥".repeat(self.game.lives_left as usize)
+                ));
+            }
+
             if let Some(problem) = &self.game.current_problem {
                 ui.heading(&problem.problem);
+                ui.weak(format!(
+                    "Problem rating: {:.0}",
+                    self.game.ratings.problem(problem)
+                ));
+                // the kind of answer decides the input, mixed rounds and missing operands change
+                // it from one problem to the next
+                let expected = problem.answer.clone();
 
                 match self.game.game_state {
                     GameState::NotStarted => {
-                        if ui.button("Start Round").clicked() {
-                            self.game.generate_problem();
-                            self.game.game_state = GameState::Playing;
-                        }
-                    }
-                    GameState::Playing => {
-                        match self.game.current_type {
-                            GameType::Addition
-                            | GameType::Subtraction
-                            | GameType::Multiplication
-                            | GameType::Division => {
-                                let response = ui.text_edit_singleline(&mut self.current_answer);
-
-                                for _ in 0..self.game.problems_per_round {
-                                    if response.changed() {
-                                        if let Ok(user_answer) = self.current_answer.parse::<i32>()
-                                        {
-                                            if self.game.check_answer(&Answer::Integer(user_answer))
-                                            {
-                                                self.current_answer.clear();
-
-                                                if self.game.is_round_completed() {
-                                                    self.game.game_state =
-                                                        GameState::RoundComplete;
-                                                } else {
-                                                    self.game.generate_problem();
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
+                        ui.horizontal(|ui| {
+                            ui.label("Seed: ");
+                            ui.text_edit_singleline(&mut self.seed_input);
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Fraction answers: ");
+                            let policy = &mut self.game.fraction_policy;
+                            ui.radio_value(policy, FractionPolicy::AnyEquivalent, "Any equivalent");
+                            ui.radio_value(policy, FractionPolicy::LowestTerms, "Lowest terms");
+                            ui.radio_value(policy, FractionPolicy::MixedNumber, "Mixed number");
+                        });
+
+                        // decimal places are kept per difficulty, this sets the chosen one
+                        ui.horizontal(|ui| {
+                            ui.label("Decimal places: ");
+                            let difficulty = self.game.current_difficulty;
+                            let mut places = self.game.decimal_places(difficulty);
+                            for n in 1..=MAX_DECIMAL_PLACES {
+                                ui.radio_value(&mut places, n, n.to_string());
                             }
+                            self.game.decimal_places.insert(difficulty, places);
+                        });
 
-                            GameType::FractionAddition | GameType::FractionSubtraction => {
-                                let mut should_check = false;
+                        ui.horizontal(|ui| {
+                            ui.label("Carries and borrows: ");
+                            let regrouping = &mut self.game.regrouping;
+                            ui.radio_value(regrouping, Regrouping::Any, "Any");
+                            ui.radio_value(regrouping, Regrouping::Required, "Always");
+                            ui.radio_value(regrouping, Regrouping::Forbidden, "Never");
+                        });
 
-                                ui.horizontal(|ui| {
-                                    let numerator_response =
-                                        ui.text_edit_singleline(&mut self.current_numerator);
+                        // negative operands only apply to whole number problems
+                        ui.checkbox(&mut self.game.signed_numbers, "Negative numbers");
+                        ui.checkbox(&mut self.game.missing_operands, "Missing operands");
+                        ui.checkbox(&mut self.game.deck_mode, "Fact deck, every fact once");
+                        if ui
+                            .checkbox(&mut self.game.adaptive, "Adaptive difficulty")
+                            .changed()
+                        {
+                            self.game.generate_problem();
+                        }
 
-                                    ui.label("/");
+                        ui.horizontal(|ui| {
+                            ui.label("Check answers: ");
+                            let mode = &mut self.game.submit_mode;
+                            ui.radio_value(mode, SubmitMode::ExactMatch, "As soon as right");
+                            ui.radio_value(mode, SubmitMode::Enter, "On Enter");
+                            ui.radio_value(mode, SubmitMode::DigitCount, "When all digits are in");
+                        });
 
-                                    let denominator_response =
-                                        ui.text_edit_singleline(&mut self.current_denominator);
+                        ui.horizontal(|ui| {
+                            ui.label("Points: ");
+                            let scoring = &mut self.game.scoring;
+                            ui.radio_value(&mut scoring.points, Points::Flat, "One per answer");
+                            ui.radio_value(
+                                &mut scoring.points,
+                                Points::SpeedWeighted,
+                                "Faster is more",
+                            );
+                            ui.checkbox(&mut scoring.streak_multiplier, "Streaks multiply");
+                            ui.checkbox(
+                                &mut scoring.difficulty_multiplier,
+                                "Difficulty multiplies",
+                            );
+                        });
 
-                                    should_check = numerator_response.changed()
-                                        || denominator_response.changed();
-                                });
+                        ui.horizontal(|ui| {
+                            ui.label("Mistakes cost ");
+                            ui.add(
+                                egui::DragValue::new(&mut self.game.scoring.mistake_penalty)
+                                    .range(0..=10)
+                                    .suffix(" points"),
+                            );
+                            let mut lives = self.game.lives.is_some();
+                            if ui.checkbox(&mut lives, "Lives").changed() {
+                                self.game.lives = lives.then_some(3);
+                            }
+                            if let Some(lives) = &mut self.game.lives {
+                                ui.add(egui::DragValue::new(lives).range(1..=10));
+                            }
+                        });
 
-                                if should_check {
-                                    if let (Ok(num), Ok(den)) = (
-                                        self.current_numerator.parse::<i32>(),
-                                        self.current_denominator.parse::<i32>(),
-                                    ) {
-                                        if den != 0 {
-                                            // Avoid division by zero
+                        ui.horizontal(|ui| {
+                            ui.label("Round: ");
+                            let sprint = &mut self.game.sprint_seconds;
+                            let problems = format!("{} problems", self.game.problems_per_round);
+                            ui.radio_value(sprint, None, problems);
+                            for seconds in SPRINT_LENGTHS {
+                                ui.radio_value(sprint, Some(seconds), format_time(seconds));
+                            }
+                        });
 
-                                            let user_answer = Answer::Fraction {
-                                                numerator: num,
+                        if ui.button("Start Round").clicked() {
+                            // every round starts from a seed so it can be replayed exactly
+                            let seed = self
+                                .seed_input
+                                .trim()
+                                .parse::<u64>()
+                                .unwrap_or_else(|_| rng::random_seed());
+                            self.game.start_round(seed);
+                        }
+                    }
+                    GameState::Playing => {
+                        match expected {
+                            Answer::Integer(_) => {
+                                self.text_answer_ui(ui, &expected, Answer::parse_integer, "");
+                            }
 
-                                                denominator: den,
-                                            };
+                            Answer::Fraction(_) => {
+                                self.fraction_answer_ui(ui, &expected);
+                            }
 
-                                            if self.game.check_answer(&user_answer) {
-                                                self.current_numerator.clear();
-                                                self.current_denominator.clear();
-                                                self.game.generate_problem();
-                                            }
-                                        }
-                                    }
-                                }
+                            Answer::QuotientRemainder { .. } => {
+                                let parse = Answer::parse_quotient_remainder;
+                                self.text_answer_ui(ui, &expected, parse, "17 r 3");
+                            }
+
+                            Answer::Decimal(_) => {
+                                self.text_answer_ui(ui, &expected, Answer::parse_decimal, "");
                             }
                         }
+
+                        if let Some(feedback) = self.feedback {
+                            ui.label(feedback);
+                        }
                     }
-                    GameState::RoundComplete => {
-                        ui.heading("Round Completed!");
+                    GameState::RoundComplete | GameState::TimeUp | GameState::OutOfLives => {
+                        match self.game.game_state {
+                            GameState::TimeUp => {
+                                ui.heading("Time's Up!");
+                                let seconds = self.game.sprint_seconds.unwrap_or(0);
+                                ui.label(format!(
+                                    "Solved {} problems in {}",
+                                    self.game.round_history.len(),
+                                    format_time(seconds)
+                                ));
+                            }
+                            GameState::OutOfLives => {
+                                ui.heading("Out of Lives!");
+                            }
+                            _ => {
+                                ui.heading("Round Completed!");
+                            }
+                        }
                         ui.label(format!("Final Score: {}", self.game.score));
+                        score_breakdown_ui(ui, &self.game.round_score);
+                        ui.label(format!("Seed: {}", self.game.seed()));
+
+                        let (correct, attempts) = self.game.round_accuracy();
+                        if attempts > 0 {
+                            ui.label(format!(
+                                "Accuracy: {} of {} answers right ({}%)",
+                                correct,
+                                attempts,
+                                correct * 100 / attempts
+                            ));
+                        }
+
+                        if let Some(stats) = self.game.round_stats() {
+                            ui.add_space(10.0);
+                            ui.label(format!(
+                                "Mean {}, median {}, fastest {}, slowest {}",
+                                format_seconds(stats.mean),
+                                format_seconds(stats.median),
+                                format_seconds(stats.fastest),
+                                format_seconds(stats.slowest)
+                            ));
+                            ui.collapsing("Problems", |ui| {
+                                egui::Grid::new("round_history")
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for problem in &self.game.round_history {
+                                            ui.label(&problem.problem);
+                                            ui.label(problem.answer.to_string());
+                                            if let Some(latency) = problem.latency() {
+                                                ui.label(format_seconds(latency));
+                                            }
+                                            let wrong = problem
+                                                .attempts
+                                                .iter()
+                                                .filter(|attempt| !attempt.correct)
+                                                .count();
+                                            if wrong > 0 {
+                                                ui.label(format!("{} wrong", wrong));
+                                            }
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+                        }
 
                         if ui.button("Play Again").clicked() {
                             // Reset the session for a new round
+                            self.seed_input = rng::random_seed().to_string();
                             self.game.score = 0;
                             self.game.current_round_completed_problems = 0;
                             self.game.game_state = GameState::NotStarted;
                         }
                     }
                 }
-            } else {
-                self.game.generate_problem();
+            } else if self.game.generate_problem().is_none() {
+                // nothing fits until the level or the settings change, the side panel changes the
+                // level and this button the setting that can't be met
+                ui.label(
+                    "No problem at this level fits the carries and borrows or the complexity.",
+                );
+                if ui.button("Allow any carries and borrows").clicked() {
+                    self.game.regrouping = Regrouping::Any;
+                }
             }
         });
     }
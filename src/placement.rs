@@ -0,0 +1,199 @@
+use crate::difficulty;
+use crate::game::{GameDifficulty, GameType};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+// every operation has its own starting level, mixed rounds are made of them
+const GAME_TYPES: [GameType; 14] = [
+    GameType::Addition,
+    GameType::Subtraction,
+    GameType::Multiplication,
+    GameType::Division,
+    GameType::DivisionWithRemainder,
+    GameType::FractionAddition,
+    GameType::FractionSubtraction,
+    GameType::FractionMultiplication,
+    GameType::FractionDivision,
+    GameType::DecimalAddition,
+    GameType::DecimalSubtraction,
+    GameType::DecimalMultiplication,
+    GameType::DecimalDivision,
+    GameType::Chain,
+];
+
+// a level is passed with a right answer within 10 seconds per preset step, 10 s at Basic and
+// 40 s at Mastery
+const PASS_SECONDS_PER_LEVEL: i64 = 10;
+
+// PlacementResult is what the placement found out about one operation
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlacementResult {
+    pub difficulty: GameDifficulty,
+    pub answered: u32,
+    pub correct: u32,
+    pub mean_seconds: f64,
+}
+
+// Search is a binary search for the highest preset of a game type the player passes, Basic is
+// where everybody starts so it never needs a problem of its own
+#[derive(Debug, Clone)]
+struct Search {
+    game_type: GameType,
+    low: usize,
+    high: usize,
+    answered: u32,
+    correct: u32,
+    time: Duration,
+}
+
+impl Search {
+    fn probe(&self) -> usize {
+        (self.low + self.high).div_ceil(2)
+    }
+
+    fn is_done(&self) -> bool {
+        self.low >= self.high
+    }
+
+    fn record(&mut self, correct: bool, latency: Duration) {
+        let probe = self.probe();
+        self.answered += 1;
+        self.correct += correct as u32;
+        self.time += latency;
+        let limit = Duration::seconds(PASS_SECONDS_PER_LEVEL * (probe as i64 + 1));
+        if correct && latency <= limit {
+            self.low = probe;
+        } else {
+            self.high = probe - 1;
+        }
+    }
+
+    fn result(&self) -> PlacementResult {
+        PlacementResult {
+            difficulty: difficulty::presets()[self.low].difficulty,
+            answered: self.answered,
+            correct: self.correct,
+            mean_seconds: match self.answered {
+                0 => 0.0,
+                n => self.time.num_milliseconds() as f64 / 1000.0 / n as f64,
+            },
+        }
+    }
+}
+
+// Placement is a short test across every operation, about two problems each. The operations
+// are tested one after the other.
+#[derive(Debug, Clone)]
+pub struct Placement {
+    searches: Vec<Search>,
+    // the level the player was on before the test
+    pub return_type: GameType,
+    pub return_difficulty: GameDifficulty,
+}
+
+impl Placement {
+    pub fn new(return_type: GameType, return_difficulty: GameDifficulty) -> Self {
+        let high = difficulty::presets().len() - 1;
+        Self {
+            searches: GAME_TYPES
+                .iter()
+                .map(|game_type| Search {
+                    game_type: *game_type,
+                    low: 0,
+                    high,
+                    answered: 0,
+                    correct: 0,
+                    time: Duration::zero(),
+                })
+                .collect(),
+            return_type,
+            return_difficulty,
+        }
+    }
+
+    // next is the level the next problem is asked at, None once the test is over
+    pub fn next(&self) -> Option<(GameType, GameDifficulty)> {
+        let search = self.searches.iter().find(|search| !search.is_done())?;
+        let difficulty = difficulty::presets()[search.probe()].difficulty;
+        Some((search.game_type, difficulty))
+    }
+
+    pub fn record(&mut self, correct: bool, latency: Duration) {
+        if let Some(search) = self.searches.iter_mut().find(|search| !search.is_done()) {
+            search.record(correct, latency);
+        }
+    }
+
+    // progress is how many operations are placed out of how many
+    pub fn progress(&self) -> (usize, usize) {
+        let done = self
+            .searches
+            .iter()
+            .filter(|search| search.is_done())
+            .count();
+        (done, self.searches.len())
+    }
+
+    pub fn results(&self) -> Vec<(GameType, PlacementResult)> {
+        self.searches
+            .iter()
+            .map(|search| (search.game_type, search.result()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_finds_highest_passed_level() {
+        let mut placement = Placement::new(GameType::Addition, GameDifficulty::Basic);
+        let fast = Duration::seconds(3);
+        // Hard is asked first, then Mastery after a pass
+        assert_eq!(
+            placement.next(),
+            Some((GameType::Addition, GameDifficulty::Hard))
+        );
+        placement.record(true, fast);
+        assert_eq!(
+            placement.next(),
+            Some((GameType::Addition, GameDifficulty::Mastery))
+        );
+        placement.record(false, fast);
+        assert_eq!(
+            placement.next(),
+            Some((GameType::Subtraction, GameDifficulty::Hard))
+        );
+
+        // too slow at Hard and wrong at Medium places at Basic
+        placement.record(true, Duration::seconds(90));
+        assert_eq!(
+            placement.next(),
+            Some((GameType::Subtraction, GameDifficulty::Medium))
+        );
+        placement.record(false, fast);
+        assert_eq!(placement.progress(), (2, 14));
+
+        let results = placement.results();
+        assert_eq!(results[0].1.difficulty, GameDifficulty::Hard);
+        assert_eq!((results[0].1.answered, results[0].1.correct), (2, 1));
+        assert_eq!(results[0].1.mean_seconds, 3.0);
+        assert_eq!(results[1].1.difficulty, GameDifficulty::Basic);
+    }
+
+    #[test]
+    fn test_placement_ends() {
+        let mut placement = Placement::new(GameType::Addition, GameDifficulty::Basic);
+        let mut problems = 0;
+        while placement.next().is_some() {
+            placement.record(true, Duration::seconds(1));
+            problems += 1;
+        }
+        assert_eq!(problems, 28);
+        assert!(placement
+            .results()
+            .iter()
+            .all(|(_, result)| result.difficulty == GameDifficulty::Mastery));
+    }
+}
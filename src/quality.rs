@@ -0,0 +1,157 @@
+use crate::complexity::{self, Complexity};
+use crate::expr::{BinaryOp, Expr};
+use crate::game::Answer;
+use crate::rational::Rational;
+use serde::{Deserialize, Serialize};
+
+// Regrouping is the carry/borrow constraint on whole number additions and subtractions
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Regrouping {
+    Any,
+    // every addition needs a carry and every subtraction a borrow
+    Required,
+    // no column carries or borrows, the sums can be done digit by digit
+    Forbidden,
+}
+
+impl Regrouping {
+    // allows tells whether the column work of a op b fits the constraint, other operations
+    // have no carries or borrows to constrain
+    pub fn allows(&self, op: BinaryOp, a: i64, b: i64) -> bool {
+        let regroups = match op {
+            BinaryOp::Add => needs_carry(a, b),
+            BinaryOp::Sub => needs_borrow(a, b),
+            _ => return true,
+        };
+        match self {
+            Regrouping::Any => true,
+            Regrouping::Required => regroups,
+            Regrouping::Forbidden => !regroups,
+        }
+    }
+}
+
+// needs_carry tells whether a + b carries or borrows in any column the way it is worked out by
+// hand. -3 + 7 is worked out as 7 - 3, so it doesn't carry.
+pub fn needs_carry(a: i64, b: i64) -> bool {
+    regroups(complexity::signed_sum(a, b))
+}
+
+// needs_borrow is the same for a - b. 3 - 7 is worked out as 7 - 3, so it doesn't borrow, and
+// 3 - -9 is 3 + 9, which carries.
+pub fn needs_borrow(a: i64, b: i64) -> bool {
+    regroups(complexity::signed_sum(a, -b))
+}
+
+fn regroups(column_work: Complexity) -> bool {
+    column_work.carries + column_work.borrows > 0
+}
+
+// is_trivial rejects problems that don't need any work: a zero result, adding or subtracting
+// zero, multiplying or dividing by one and combining a fraction with itself
+pub fn is_trivial(expr: &Expr, answer: &Answer) -> bool {
+    let zero_answer = match answer {
+        Answer::Integer(n) => *n == 0,
+        Answer::Fraction(r) => r.numerator() == 0,
+        Answer::Decimal(d) => d.mantissa() == 0,
+        Answer::QuotientRemainder { .. } => false,
+    };
+    zero_answer || has_trivial_step(expr)
+}
+
+fn has_trivial_step(expr: &Expr) -> bool {
+    match expr {
+        Expr::Number(_) | Expr::Fraction { .. } | Expr::Decimal(_) => false,
+        Expr::Paren(inner) | Expr::Hidden(inner) => has_trivial_step(inner),
+        Expr::Binary(op, left, right) => {
+            let (a, b) = (value_of(left), value_of(right));
+            let is = |value: Option<Rational>, n: i64| value == Some(Rational::from_integer(n));
+            let trivial = match op {
+                BinaryOp::Add => is(a, 0) || is(b, 0),
+                BinaryOp::Sub => is(b, 0),
+                BinaryOp::Mul => is(a, 1) || is(b, 1),
+                BinaryOp::Div | BinaryOp::DivRem => is(b, 1),
+            };
+            let same_fraction = matches!(**left, Expr::Fraction { .. }) && left == right;
+            trivial || same_fraction || has_trivial_step(left) || has_trivial_step(right)
+        }
+    }
+}
+
+fn value_of(expr: &Expr) -> Option<Rational> {
+    match expr.evaluate()? {
+        Answer::Integer(n) => Some(Rational::from_integer(n as i64)),
+        Answer::Fraction(r) => Some(r),
+        Answer::Decimal(d) => d.to_rational(),
+        Answer::QuotientRemainder { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trivial(expr: Expr) -> bool {
+        let answer = expr.evaluate().unwrap();
+        is_trivial(&expr, &answer)
+    }
+
+    #[test]
+    fn test_carry_and_borrow() {
+        assert!(needs_carry(27, 15));
+        assert!(needs_carry(95, 5));
+        assert!(!needs_carry(23, 14));
+        assert!(needs_borrow(42, 17));
+        assert!(needs_borrow(17, 42));
+        assert!(!needs_borrow(58, 23));
+        assert!(!needs_borrow(3, 7));
+
+        // negative numbers regroup the way the signed sum is worked out
+        assert!(!needs_carry(-3, 7));
+        assert!(needs_carry(-3, -9));
+        assert!(!needs_borrow(-8, -3));
+        assert!(needs_borrow(3, -9));
+    }
+
+    #[test]
+    fn test_trivial_problems() {
+        let n = Expr::number;
+        assert!(trivial(Expr::binary(BinaryOp::Mul, n(1), n(1))));
+        assert!(trivial(Expr::binary(BinaryOp::Mul, n(7), n(1))));
+        assert!(trivial(Expr::binary(BinaryOp::Sub, n(5), n(5))));
+        assert!(trivial(Expr::binary(BinaryOp::Div, n(8), n(1))));
+        assert!(trivial(Expr::binary(
+            BinaryOp::Add,
+            Expr::fraction(1, 2),
+            Expr::fraction(1, 2)
+        )));
+        assert!(trivial(Expr::binary(
+            BinaryOp::Mul,
+            Expr::fraction(3, 4),
+            Expr::fraction(5, 5)
+        )));
+        // a trivial step anywhere in a chain counts
+        assert!(trivial(Expr::binary(
+            BinaryOp::Add,
+            n(4),
+            Expr::binary(BinaryOp::Mul, n(3), n(1))
+        )));
+
+        assert!(!trivial(Expr::binary(BinaryOp::Mul, n(2), n(3))));
+        assert!(!trivial(Expr::binary(BinaryOp::Sub, n(1), n(5))));
+        assert!(!trivial(Expr::binary(
+            BinaryOp::Add,
+            Expr::fraction(1, 4),
+            Expr::fraction(2, 4)
+        )));
+    }
+
+    #[test]
+    fn test_regrouping() {
+        assert!(Regrouping::Required.allows(BinaryOp::Add, 18, 5));
+        assert!(!Regrouping::Required.allows(BinaryOp::Add, 12, 5));
+        assert!(Regrouping::Forbidden.allows(BinaryOp::Sub, 18, 5));
+        assert!(!Regrouping::Forbidden.allows(BinaryOp::Sub, 12, 5));
+        assert!(Regrouping::Required.allows(BinaryOp::Mul, 12, 5));
+    }
+}
@@ -0,0 +1,208 @@
+use crate::game::{GameDifficulty, GameType, Problem};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+// every level and every problem starts here, the usual Elo scale
+pub const INITIAL_RATING: f64 = 1000.0;
+// how far one answer moves the rating of the player and of the problem. Problems are answered
+// by many rounds so they move slower.
+const PLAYER_K: f64 = 32.0;
+const PROBLEM_K: f64 = 16.0;
+// the rating a level needs for each golden star, ten stars at 1400
+const STAR_STEP: f64 = 40.0;
+// big levels hardly ever repeat a problem, only the ones answered last are kept so the saved
+// ratings don't grow forever
+const MAX_RATED_PROBLEMS: usize = 1000;
+
+// expected is the chance the Elo model gives a player to answer a problem right
+pub fn expected(player: f64, problem: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((problem - player) / 400.0))
+}
+
+// stars is how many of the ten stars a rating turns golden
+pub fn stars(rating: f64) -> u32 {
+    ((rating - INITIAL_RATING) / STAR_STEP).clamp(0.0, 10.0) as u32
+}
+
+// Ratings pits the player of every level, a game type at a difficulty, against the problems.
+// A right answer to a problem rated above the player gains more than one to an easy problem,
+// and the problem loses what the player gains.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "SavedRatings", into = "SavedRatings")]
+pub struct Ratings {
+    players: HashMap<(GameType, GameDifficulty), f64>,
+    // problems are known by their text
+    problems: HashMap<String, f64>,
+    // the rated problems from the least recently answered one on
+    answered: VecDeque<String>,
+}
+
+impl Ratings {
+    pub fn player(&self, game_type: GameType, difficulty: GameDifficulty) -> f64 {
+        self.players
+            .get(&(game_type, difficulty))
+            .copied()
+            .unwrap_or(INITIAL_RATING)
+    }
+
+    // problem is the rating of a problem, a new one is estimated from its complexity within
+    // the level
+    pub fn problem(&self, problem: &Problem) -> f64 {
+        self.problems
+            .get(&problem.problem)
+            .copied()
+            .unwrap_or_else(|| INITIAL_RATING + 8.0 * (problem.complexity.percent() as f64 - 50.0))
+    }
+
+    pub fn record(
+        &mut self,
+        game_type: GameType,
+        difficulty: GameDifficulty,
+        problem: &Problem,
+        correct: bool,
+    ) {
+        let player = self.player(game_type, difficulty);
+        let rating = self.problem(problem);
+        let surprise = if correct { 1.0 } else { 0.0 } - expected(player, rating);
+        self.players
+            .insert((game_type, difficulty), player + PLAYER_K * surprise);
+        self.problems
+            .insert(problem.problem.clone(), rating - PROBLEM_K * surprise);
+
+        if let Some(index) = self.answered.iter().position(|p| *p == problem.problem) {
+            self.answered.remove(index);
+        }
+        self.answered.push_back(problem.problem.clone());
+        if self.answered.len() > MAX_RATED_PROBLEMS {
+            if let Some(oldest) = self.answered.pop_front() {
+                self.problems.remove(&oldest);
+            }
+        }
+    }
+}
+
+// SavedRatings is the shape ratings are saved in, the maps have keys that are not strings.
+// Problems are saved from the least recently answered one on.
+#[derive(Serialize, Deserialize)]
+struct SavedRatings {
+    players: Vec<(GameType, GameDifficulty, f64)>,
+    problems: Vec<(String, f64)>,
+}
+
+impl From<SavedRatings> for Ratings {
+    fn from(saved: SavedRatings) -> Self {
+        Self {
+            players: saved
+                .players
+                .into_iter()
+                .map(|(game_type, difficulty, rating)| ((game_type, difficulty), rating))
+                .collect(),
+            answered: saved
+                .problems
+                .iter()
+                .map(|(problem, _)| problem.clone())
+                .collect(),
+            problems: saved.problems.into_iter().collect(),
+        }
+    }
+}
+
+impl From<Ratings> for SavedRatings {
+    fn from(ratings: Ratings) -> Self {
+        Self {
+            players: ratings
+                .players
+                .into_iter()
+                .map(|((game_type, difficulty), rating)| (game_type, difficulty, rating))
+                .collect(),
+            problems: ratings
+                .answered
+                .into_iter()
+                .filter_map(|problem| {
+                    let rating = *ratings.problems.get(&problem)?;
+                    Some((problem, rating))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::{BinaryOp, Expr};
+
+    fn problem(a: i32, b: i32) -> Problem {
+        Problem::new(
+            GameType::Addition,
+            Expr::binary(BinaryOp::Add, Expr::number(a), Expr::number(b)),
+            a.into(),
+            b.into(),
+        )
+    }
+
+    #[test]
+    fn test_expected() {
+        assert_eq!(expected(1000.0, 1000.0), 0.5);
+        assert!(expected(1400.0, 1000.0) > 0.9);
+        assert!(expected(1000.0, 1400.0) < 0.1);
+    }
+
+    #[test]
+    fn test_answers_move_both_ratings() {
+        let mut ratings = Ratings::default();
+        let (game_type, difficulty) = (GameType::Addition, GameDifficulty::Medium);
+        let hard = problem(47, 38);
+        let easy = problem(50, 40);
+        assert!(ratings.problem(&easy) < ratings.problem(&hard));
+
+        let before = ratings.problem(&hard);
+        ratings.record(game_type, difficulty, &hard, true);
+        let gained = ratings.player(game_type, difficulty) - INITIAL_RATING;
+        assert!(gained > 0.0);
+        assert!(ratings.problem(&hard) < before);
+        // other levels are rated on their own
+        assert_eq!(
+            ratings.player(game_type, GameDifficulty::Hard),
+            INITIAL_RATING
+        );
+
+        // missing an easy problem costs more than a right answer to it gains
+        let mut missed = Ratings::default();
+        missed.record(game_type, difficulty, &easy, false);
+        let lost = INITIAL_RATING - missed.player(game_type, difficulty);
+        let mut solved = Ratings::default();
+        solved.record(game_type, difficulty, &easy, true);
+        assert!(lost > solved.player(game_type, difficulty) - INITIAL_RATING);
+    }
+
+    #[test]
+    fn test_only_recent_problems_are_kept() {
+        let mut ratings = Ratings::default();
+        let (game_type, difficulty) = (GameType::Addition, GameDifficulty::Medium);
+        let first = problem(1, 10);
+        ratings.record(game_type, difficulty, &first, true);
+        let rated = ratings.problem(&first);
+        for a in 2..=MAX_RATED_PROBLEMS as i32 {
+            ratings.record(game_type, difficulty, &problem(a, 10), true);
+        }
+        // answering the first problem again keeps it, the second one is the oldest now
+        ratings.record(game_type, difficulty, &first, true);
+        assert!(ratings.problem(&first) < rated);
+        ratings.record(game_type, difficulty, &problem(5000, 10), true);
+        assert_eq!(ratings.problems.len(), MAX_RATED_PROBLEMS);
+        assert!(!ratings.problems.contains_key("2 + 10"));
+
+        let saved = SavedRatings::from(ratings.clone());
+        assert_eq!(saved.problems.last().unwrap().0, "5000 + 10");
+        assert_eq!(Ratings::from(saved), ratings);
+    }
+
+    #[test]
+    fn test_stars() {
+        assert_eq!(stars(800.0), 0);
+        assert_eq!(stars(INITIAL_RATING), 0);
+        assert_eq!(stars(1200.0), 5);
+        assert_eq!(stars(2000.0), 10);
+    }
+}
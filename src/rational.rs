@@ -0,0 +1,232 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+// Rational is an exact fraction. It is always kept normalized: the sign lives in the numerator,
+// the denominator is positive and both are reduced by their gcd, so equal values are equal
+// structs. Arithmetic is widened to i128 and returns None when the result leaves i64.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseRationalError {
+    Empty,
+    InvalidNumber,
+    ZeroDenominator,
+}
+
+impl fmt::Display for ParseRationalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRationalError::Empty => write!(f, "no number given"),
+            ParseRationalError::InvalidNumber => write!(f, "not a number"),
+            ParseRationalError::ZeroDenominator => write!(f, "the denominator can't be zero"),
+        }
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Rational {
+    pub fn new(numerator: i64, denominator: i64) -> Option<Self> {
+        Self::from_wide(numerator as i128, denominator as i128)
+    }
+
+    pub fn from_integer(n: i64) -> Self {
+        Self {
+            numerator: n,
+            denominator: 1,
+        }
+    }
+
+    fn from_wide(numerator: i128, denominator: i128) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        Some(Self {
+            numerator: i64::try_from(numerator / divisor).ok()?,
+            denominator: i64::try_from(denominator / divisor).ok()?,
+        })
+    }
+
+    pub fn numerator(&self) -> i64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i64 {
+        self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let (a, b, c, d) = self.wide(other);
+        Self::from_wide(a * d + b * c, b * d)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let (a, b, c, d) = self.wide(other);
+        Self::from_wide(a * d - b * c, b * d)
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let (a, b, c, d) = self.wide(other);
+        Self::from_wide(a * c, b * d)
+    }
+
+    // checked_div returns None when dividing by zero
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        let (a, b, c, d) = self.wide(other);
+        Self::from_wide(a * d, b * c)
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Self {
+            numerator: self.numerator.checked_neg()?,
+            denominator: self.denominator,
+        })
+    }
+
+    fn wide(self, other: Self) -> (i128, i128, i128, i128) {
+        (
+            self.numerator as i128,
+            self.denominator as i128,
+            other.numerator as i128,
+            other.denominator as i128,
+        )
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Self::from_integer(n)
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b, c, d) = self.wide(*other);
+        (a * d).cmp(&(c * b))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+// Parses "3", "-3", "6/8" and "-6 / 8"
+impl FromStr for Rational {
+    type Err = ParseRationalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseRationalError::Empty);
+        }
+
+        let parse = |part: &str| {
+            part.trim()
+                .parse::<i64>()
+                .map_err(|_| ParseRationalError::InvalidNumber)
+        };
+
+        match s.split_once('/') {
+            None => Ok(Self::from_integer(parse(s)?)),
+            Some((numerator, denominator)) => Self::new(parse(numerator)?, parse(denominator)?)
+                .ok_or(ParseRationalError::ZeroDenominator),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(n: i64, d: i64) -> Rational {
+        Rational::new(n, d).unwrap()
+    }
+
+    #[test]
+    fn test_normalization() {
+        assert_eq!(r(2, 4), r(1, 2));
+        assert_eq!(r(1, -2).numerator(), -1);
+        assert_eq!(r(1, -2).denominator(), 2);
+        assert_eq!(r(-3, -9), r(1, 3));
+        assert_eq!(r(0, -5), Rational::from_integer(0));
+        assert_eq!(Rational::new(1, 0), None);
+    }
+
+    #[test]
+    fn test_order_is_by_value() {
+        assert!(r(1, 3) < r(1, 2));
+        assert!(r(10, 3) > r(9, 3));
+        assert!(r(-1, 2) < r(1, 100));
+        assert!(r(9, 10) < Rational::from_integer(1));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(r(1, 2).checked_add(r(1, 3)), Some(r(5, 6)));
+        assert_eq!(r(1, 2).checked_sub(r(3, 4)), Some(r(-1, 4)));
+        assert_eq!(r(2, 3).checked_mul(r(3, 4)), Some(r(1, 2)));
+        assert_eq!(r(2, 3).checked_div(r(4, 9)), Some(r(3, 2)));
+        assert_eq!(r(2, 3).checked_div(Rational::from_integer(0)), None);
+        assert_eq!(r(2, 3).checked_neg(), Some(r(-2, 3)));
+    }
+
+    #[test]
+    fn test_overflow_is_none() {
+        let big = Rational::from_integer(i64::MAX);
+        assert_eq!(big.checked_mul(Rational::from_integer(2)), None);
+        assert_eq!(big.checked_add(r(1, 2)), None);
+        // widened intermediates don't overflow when the result fits
+        assert_eq!(big.checked_mul(r(1, 2)), Some(r(i64::MAX, 2)));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("6/8".parse(), Ok(r(3, 4)));
+        assert_eq!(" -6 / 8 ".parse(), Ok(r(-3, 4)));
+        assert_eq!("7".parse(), Ok(Rational::from_integer(7)));
+        assert_eq!("".parse::<Rational>(), Err(ParseRationalError::Empty));
+        assert_eq!(
+            "1/x".parse::<Rational>(),
+            Err(ParseRationalError::InvalidNumber)
+        );
+        assert_eq!(
+            "1/0".parse::<Rational>(),
+            Err(ParseRationalError::ZeroDenominator)
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(r(6, 8).to_string(), "3/4");
+        assert_eq!(r(-1, 2).to_string(), "-1/2");
+        assert_eq!(r(4, 2).to_string(), "2");
+    }
+}
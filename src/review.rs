@@ -0,0 +1,197 @@
+use crate::expr::{BinaryOp, Expr};
+use crate::game::{GameType, Problem};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// how long a fact waits in each Leitner box before it is due again. A missed fact goes back to
+// the first box and comes up again in the same session.
+const BOX_INTERVALS: [Duration; 6] = [
+    Duration::minutes(1),
+    Duration::days(1),
+    Duration::days(3),
+    Duration::weeks(1),
+    Duration::weeks(2),
+    Duration::days(30),
+];
+
+// a correct answer slower than this is not known well enough to move up a box
+const FAST_ANSWER: Duration = Duration::seconds(5);
+
+// Fact is a single whole number fact, 7 * 8 or 56 / 8, with the operands as the problem
+// shows them. Other game types have too many facts to schedule one by one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fact {
+    pub game_type: GameType,
+    pub operand1: i32,
+    pub operand2: i32,
+}
+
+impl Fact {
+    // of finds the fact a problem asks, missing operand problems ask the same fact
+    pub fn of(problem: &Problem) -> Option<Self> {
+        match problem.game_type {
+            GameType::Addition
+            | GameType::Subtraction
+            | GameType::Multiplication
+            | GameType::Division => Some(Self {
+                game_type: problem.game_type,
+                operand1: i32::try_from(problem.operand1).ok()?,
+                operand2: i32::try_from(problem.operand2).ok()?,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn problem(&self) -> Problem {
+        let op = match self.game_type {
+            GameType::Subtraction => BinaryOp::Sub,
+            GameType::Multiplication => BinaryOp::Mul,
+            GameType::Division => BinaryOp::Div,
+            _ => BinaryOp::Add,
+        };
+        Problem::new(
+            self.game_type,
+            Expr::binary(op, Expr::number(self.operand1), Expr::number(self.operand2)),
+            self.operand1.into(),
+            self.operand2.into(),
+        )
+    }
+}
+
+// Card is what the schedule knows about a fact. Times are unix seconds so they save as plain
+// numbers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Card {
+    pub fact: Fact,
+    pub leitner_box: usize,
+    pub due: i64,
+    pub reviews: u32,
+    pub lapses: u32,
+}
+
+// Schedule is a Leitner system over every fact answered so far: a fast correct answer moves the
+// fact up a box and further into the future, a slow one keeps it in its box and a miss sends
+// it back to the first box.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "Vec<Card>", into = "Vec<Card>")]
+pub struct Schedule {
+    cards: HashMap<Fact, Card>,
+}
+
+impl From<Vec<Card>> for Schedule {
+    fn from(cards: Vec<Card>) -> Self {
+        Self {
+            cards: cards.into_iter().map(|card| (card.fact, card)).collect(),
+        }
+    }
+}
+
+impl From<Schedule> for Vec<Card> {
+    fn from(schedule: Schedule) -> Self {
+        schedule.cards.into_values().collect()
+    }
+}
+
+impl Schedule {
+    pub fn record(&mut self, fact: Fact, correct: bool, latency: Duration, now: DateTime<Utc>) {
+        let card = self.cards.entry(fact).or_insert(Card {
+            fact,
+            leitner_box: 0,
+            due: now.timestamp(),
+            reviews: 0,
+            lapses: 0,
+        });
+        card.reviews += 1;
+        if !correct {
+            card.lapses += 1;
+            card.leitner_box = 0;
+        } else if latency <= FAST_ANSWER {
+            card.leitner_box = (card.leitner_box + 1).min(BOX_INTERVALS.len() - 1);
+        }
+        card.due = (now + BOX_INTERVALS[card.leitner_box]).timestamp();
+    }
+
+    pub fn card(&self, fact: &Fact) -> Option<&Card> {
+        self.cards.get(fact)
+    }
+
+    // next_due is the fact that has waited the longest past its due time
+    pub fn next_due(&self, now: DateTime<Utc>) -> Option<Fact> {
+        self.cards
+            .values()
+            .filter(|card| card.due <= now.timestamp())
+            .min_by_key(|card| (card.due, card.leitner_box))
+            .map(|card| card.fact)
+    }
+
+    pub fn due_count(&self, now: DateTime<Utc>) -> usize {
+        self.cards
+            .values()
+            .filter(|card| card.due <= now.timestamp())
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fact(a: i32, b: i32) -> Fact {
+        Fact {
+            game_type: GameType::Multiplication,
+            operand1: a,
+            operand2: b,
+        }
+    }
+
+    #[test]
+    fn test_fast_answers_move_facts_up() {
+        let now = Utc::now();
+        let mut schedule = Schedule::default();
+        schedule.record(fact(7, 8), true, Duration::seconds(2), now);
+        let card = schedule.card(&fact(7, 8)).unwrap();
+        assert_eq!(card.leitner_box, 1);
+        assert_eq!(card.due, (now + Duration::days(1)).timestamp());
+
+        // slow answers stay in their box
+        schedule.record(fact(7, 8), true, Duration::seconds(20), now);
+        assert_eq!(schedule.card(&fact(7, 8)).unwrap().leitner_box, 1);
+
+        schedule.record(fact(7, 8), false, Duration::seconds(2), now);
+        let card = schedule.card(&fact(7, 8)).unwrap();
+        assert_eq!((card.leitner_box, card.reviews, card.lapses), (0, 3, 1));
+    }
+
+    #[test]
+    fn test_due_facts() {
+        let now = Utc::now();
+        let mut schedule = Schedule::default();
+        schedule.record(fact(6, 7), true, Duration::seconds(1), now);
+        schedule.record(fact(7, 8), false, Duration::seconds(1), now);
+        schedule.record(
+            fact(8, 9),
+            false,
+            Duration::seconds(1),
+            now + Duration::seconds(10),
+        );
+        assert_eq!(schedule.due_count(now), 0);
+
+        let later = now + Duration::minutes(5);
+        assert_eq!(schedule.due_count(later), 2);
+        assert_eq!(schedule.next_due(later), Some(fact(7, 8)));
+        assert_eq!(schedule.due_count(now + Duration::days(2)), 3);
+    }
+
+    #[test]
+    fn test_fact_problem() {
+        let division = Fact {
+            game_type: GameType::Division,
+            operand1: 56,
+            operand2: 8,
+        };
+        let problem = division.problem();
+        assert_eq!(problem.problem, "56 / 8");
+        assert_eq!(Fact::of(&problem), Some(division));
+    }
+}
@@ -0,0 +1,124 @@
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+
+// RandomSource is everything the game needs from a random number generator. Generators only
+// ever ask for inclusive integer ranges so a round can be replayed from the same seed.
+pub trait RandomSource {
+    fn gen_range(&mut self, min: i32, max: i32) -> i32;
+
+    // choose_weighted returns the index of the picked weight, None when all weights are zero
+    fn choose_weighted(&mut self, weights: &[u32]) -> Option<usize> {
+        let total: u32 = weights.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        let mut pick = self.gen_range(0, total as i32 - 1) as u32;
+        for (i, weight) in weights.iter().enumerate() {
+            if pick < *weight {
+                return Some(i);
+            }
+            pick -= weight;
+        }
+        None
+    }
+}
+
+// SeededRng is the random source used by every build. The seed is kept so it can be shown to
+// the player and used to reproduce a round exactly.
+pub struct SeededRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn from_entropy() -> Self {
+        Self::new(random_seed())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RandomSource for SeededRng {
+    fn gen_range(&mut self, min: i32, max: i32) -> i32 {
+        self.rng.gen_range(min..=max)
+    }
+}
+
+pub fn random_seed() -> u64 {
+    rand::thread_rng().next_u64()
+}
+
+// PairRng replays a fixed pair of operands so tests can pin the exact problem text.
+#[cfg(test)]
+pub struct PairRng {
+    pair: fn(i32, i32) -> (i32, i32),
+    next: Option<i32>,
+}
+
+#[cfg(test)]
+impl PairRng {
+    pub fn new(pair: fn(i32, i32) -> (i32, i32)) -> Self {
+        Self { pair, next: None }
+    }
+}
+
+#[cfg(test)]
+impl RandomSource for PairRng {
+    fn gen_range(&mut self, min: i32, max: i32) -> i32 {
+        match self.next.take() {
+            Some(n) => n,
+            None => {
+                let (a, b) = (self.pair)(min, max);
+                self.next = Some(b);
+                a
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = SeededRng::new(42);
+        let mut b = SeededRng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.gen_range(1, 1000), b.gen_range(1, 1000));
+        }
+    }
+
+    #[test]
+    fn test_gen_range_is_inclusive() {
+        let mut rng = SeededRng::new(7);
+        for _ in 0..100 {
+            let n = rng.gen_range(1, 2);
+            assert!(n == 1 || n == 2, "out of range: {}", n);
+        }
+        assert_eq!(rng.gen_range(5, 5), 5);
+    }
+
+    #[test]
+    fn test_choose_weighted() {
+        let mut rng = SeededRng::new(1);
+        assert_eq!(rng.choose_weighted(&[0, 0]), None);
+        assert_eq!(rng.choose_weighted(&[]), None);
+
+        let mut counts = [0; 3];
+        for _ in 0..1000 {
+            counts[rng.choose_weighted(&[1, 0, 3]).unwrap()] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        assert!(counts[2] > counts[0] * 2, "{:?}", counts);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::difficulty;
+use crate::game::GameDifficulty;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+// answers within this many seconds earn speed points, the faster the more
+const SPEED_WINDOW_SECONDS: i64 = 10;
+// every this many right answers in a row add one to the streak multiplier
+const STREAK_STEP: u32 = 5;
+const MAX_STREAK_MULTIPLIER: i32 = 3;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Points {
+    // one point for every right answer
+    Flat,
+    // one point plus one for every two seconds under the speed window
+    SpeedWeighted,
+}
+
+// ScoringPolicy decides the points of a round. The points of an answer are multiplied by the
+// streak and the difficulty multipliers when they are on, wrong answers cost the penalty.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoringPolicy {
+    pub points: Points,
+    pub streak_multiplier: bool,
+    pub difficulty_multiplier: bool,
+    pub mistake_penalty: u32,
+}
+
+impl Default for ScoringPolicy {
+    fn default() -> Self {
+        Self {
+            points: Points::Flat,
+            streak_multiplier: false,
+            difficulty_multiplier: false,
+            mistake_penalty: 0,
+        }
+    }
+}
+
+// ScoreBreakdown keeps apart where the points of a round came from
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ScoreBreakdown {
+    pub answers: i32,
+    pub speed: i32,
+    pub streak: i32,
+    pub difficulty: i32,
+    pub penalties: i32,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> i32 {
+        self.answers + self.speed + self.streak + self.difficulty - self.penalties
+    }
+
+    pub fn add(&mut self, other: ScoreBreakdown) {
+        self.answers += other.answers;
+        self.speed += other.speed;
+        self.streak += other.streak;
+        self.difficulty += other.difficulty;
+        self.penalties += other.penalties;
+    }
+}
+
+impl ScoringPolicy {
+    // award scores a right answer, streak is the number of right answers in a row including
+    // this one
+    pub fn award(
+        &self,
+        latency: Duration,
+        streak: u32,
+        difficulty: GameDifficulty,
+    ) -> ScoreBreakdown {
+        let speed = match self.points {
+            Points::Flat => 0,
+            Points::SpeedWeighted => {
+                ((SPEED_WINDOW_SECONDS - latency.num_seconds()).max(0) / 2) as i32
+            }
+        };
+        let points = 1 + speed;
+        let streak_multiplier = match self.streak_multiplier {
+            true => (1 + (streak / STREAK_STEP) as i32).min(MAX_STREAK_MULTIPLIER),
+            false => 1,
+        };
+        let difficulty_multiplier = match self.difficulty_multiplier {
+            true => difficulty_multiplier(difficulty),
+            false => 1,
+        };
+        ScoreBreakdown {
+            answers: 1,
+            speed,
+            streak: points * (streak_multiplier - 1),
+            difficulty: points * streak_multiplier * (difficulty_multiplier - 1),
+            penalties: 0,
+        }
+    }
+}
+
+// difficulty_multiplier counts the presets up from Basic, custom profiles are not ranked
+fn difficulty_multiplier(difficulty: GameDifficulty) -> i32 {
+    difficulty::presets()
+        .iter()
+        .position(|preset| preset.difficulty == difficulty)
+        .map_or(1, |index| index as i32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_points() {
+        let award =
+            ScoringPolicy::default().award(Duration::seconds(1), 20, GameDifficulty::Mastery);
+        assert_eq!(award.total(), 1);
+    }
+
+    #[test]
+    fn test_speed_streak_and_difficulty() {
+        let policy = ScoringPolicy {
+            points: Points::SpeedWeighted,
+            ..Default::default()
+        };
+        let basic = GameDifficulty::Basic;
+        assert_eq!(policy.award(Duration::seconds(2), 1, basic).speed, 4);
+        assert_eq!(policy.award(Duration::seconds(30), 1, basic).total(), 1);
+
+        let policy = ScoringPolicy {
+            points: Points::SpeedWeighted,
+            streak_multiplier: true,
+            difficulty_multiplier: true,
+            mistake_penalty: 0,
+        };
+        // 1 + 3 speed points, doubled by a streak of 5 and tripled at Hard
+        let award = policy.award(Duration::seconds(4), 5, GameDifficulty::Hard);
+        assert_eq!(
+            award,
+            ScoreBreakdown {
+                answers: 1,
+                speed: 3,
+                streak: 4,
+                difficulty: 16,
+                penalties: 0,
+            }
+        );
+        assert_eq!(award.total(), 24);
+        assert_eq!(
+            policy
+                .award(Duration::seconds(4), 100, GameDifficulty::Custom(1))
+                .total(),
+            12
+        );
+    }
+}
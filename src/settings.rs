@@ -0,0 +1,219 @@
+use crate::adaptive::Staircase;
+use crate::deck::Deck;
+use crate::difficulty::DifficultyProfile;
+use crate::focus::Focus;
+use crate::game::{Game, GameDifficulty, GameType, SubmitMode};
+use crate::placement::PlacementResult;
+use crate::quality::Regrouping;
+use crate::rating::Ratings;
+use crate::review::Schedule;
+use crate::scoring::ScoringPolicy;
+use serde::{Deserialize, Serialize};
+
+// Settings is the part of a game that is kept between sessions. Missing fields fall back to
+// their defaults so settings saved by an older version still load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub difficulty: GameDifficulty,
+    pub custom_profiles: Vec<DifficultyProfile>,
+    pub next_profile_id: u32,
+    pub regrouping: Regrouping,
+    pub signed_numbers: bool,
+    pub missing_operands: bool,
+    pub focus: Option<Focus>,
+    pub deck_mode: bool,
+    // decks keep their position so every fact comes up even across sessions
+    pub decks: Vec<(GameType, GameDifficulty, Deck)>,
+    pub schedule: Schedule,
+    pub adaptive: bool,
+    // skills are the adaptive levels per game type, a new session starts a streak from scratch
+    pub skills: Vec<(GameType, u32)>,
+    pub ratings: Ratings,
+    pub placements: Vec<(GameType, PlacementResult)>,
+    pub sprint_seconds: Option<u32>,
+    pub scoring: ScoringPolicy,
+    pub submit_mode: SubmitMode,
+    pub decimal_places: Vec<(GameDifficulty, u32)>,
+    pub lives: Option<u32>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            difficulty: GameDifficulty::Basic,
+            custom_profiles: Vec::new(),
+            next_profile_id: 0,
+            regrouping: Regrouping::Any,
+            signed_numbers: false,
+            missing_operands: false,
+            focus: None,
+            deck_mode: false,
+            decks: Vec::new(),
+            schedule: Schedule::default(),
+            adaptive: false,
+            skills: Vec::new(),
+            ratings: Ratings::default(),
+            placements: Vec::new(),
+            sprint_seconds: None,
+            scoring: ScoringPolicy::default(),
+            submit_mode: SubmitMode::ExactMatch,
+            decimal_places: Vec::new(),
+            lives: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            difficulty: game.current_difficulty,
+            custom_profiles: game.custom_profiles.clone(),
+            next_profile_id: game.next_profile_id,
+            regrouping: game.regrouping,
+            signed_numbers: game.signed_numbers,
+            missing_operands: game.missing_operands,
+            focus: game.focus.clone(),
+            deck_mode: game.deck_mode,
+            decks: game
+                .decks
+                .iter()
+                .map(|((game_type, difficulty), deck)| (*game_type, *difficulty, deck.clone()))
+                .collect(),
+            schedule: game.schedule.clone(),
+            adaptive: game.adaptive,
+            skills: game
+                .skills
+                .iter()
+                .map(|(game_type, skill)| (*game_type, skill.level()))
+                .collect(),
+            ratings: game.ratings.clone(),
+            placements: game
+                .placements
+                .iter()
+                .map(|(game_type, result)| (*game_type, *result))
+                .collect(),
+            sprint_seconds: game.sprint_seconds,
+            scoring: game.scoring,
+            submit_mode: game.submit_mode,
+            decimal_places: {
+                let mut places: Vec<_> = game
+                    .decimal_places
+                    .iter()
+                    .map(|(difficulty, places)| (*difficulty, *places))
+                    .collect();
+                // the map has no order, sorted so equal settings save the same
+                places.sort();
+                places
+            },
+            lives: game.lives,
+        }
+    }
+
+    pub fn apply(self, game: &mut Game) {
+        // settings saved before the counter start it past the saved profiles
+        game.next_profile_id = self
+            .custom_profiles
+            .iter()
+            .filter_map(|profile| match profile.difficulty {
+                GameDifficulty::Custom(id) => Some(id + 1),
+                _ => None,
+            })
+            .fold(self.next_profile_id, u32::max);
+        game.custom_profiles = self.custom_profiles;
+        game.regrouping = self.regrouping;
+        game.signed_numbers = self.signed_numbers;
+        game.missing_operands = self.missing_operands;
+        game.focus = self.focus;
+        game.deck_mode = self.deck_mode;
+        game.decks = self
+            .decks
+            .into_iter()
+            .map(|(game_type, difficulty, deck)| ((game_type, difficulty), deck))
+            .collect();
+        game.schedule = self.schedule;
+        game.adaptive = self.adaptive;
+        game.skills = self
+            .skills
+            .into_iter()
+            .map(|(game_type, level)| (game_type, Staircase::at(level)))
+            .collect();
+        game.ratings = self.ratings;
+        game.placements = self.placements.into_iter().collect();
+        game.sprint_seconds = self.sprint_seconds;
+        game.scoring = self.scoring;
+        game.submit_mode = self.submit_mode;
+        // difficulties missing from older settings keep their default places
+        game.decimal_places.extend(self.decimal_places);
+        game.lives = self.lives;
+        // the profile of a saved custom difficulty may be gone
+        game.current_difficulty = game.profile(self.difficulty).difficulty;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::{OperandRange, MAX_OPERAND};
+
+    #[test]
+    fn test_apply_restores_profiles() {
+        let mut game = Game::with_seed(1);
+        game.current_difficulty = game.add_profile("drill", GameDifficulty::Medium);
+        game.decimal_places.insert(GameDifficulty::Hard, 3);
+        let settings = Settings::from_game(&game);
+
+        let mut restored = Game::with_seed(1);
+        settings.clone().apply(&mut restored);
+        assert_eq!(Settings::from_game(&restored), settings);
+
+        let mut missing = Game::with_seed(1);
+        Settings {
+            custom_profiles: Vec::new(),
+            ..settings
+        }
+        .apply(&mut missing);
+        assert_eq!(missing.current_difficulty, GameDifficulty::Basic);
+    }
+
+    #[test]
+    fn test_bad_profile_loads_clamped() {
+        let saved = r#"(
+            difficulty: Custom(1),
+            custom_profiles: [(
+                difficulty: Custom(1),
+                name: "edited",
+                operand1: (min: 0, max: -4),
+                operand2: (min: 50000, max: 0),
+            )],
+        )"#;
+        let settings: Settings = ron::from_str(saved).unwrap();
+        assert_eq!(settings.next_profile_id, 0);
+        let profile = &settings.custom_profiles[0];
+        assert_eq!(profile.operand1, OperandRange { min: 1, max: 1 });
+        assert_eq!(
+            profile.operand2,
+            OperandRange {
+                min: 1,
+                max: MAX_OPERAND
+            }
+        );
+
+        // divisors and denominators are never zero
+        let mut game = Game::with_seed(3);
+        settings.apply(&mut game);
+        assert_eq!(game.current_difficulty, GameDifficulty::Custom(1));
+        assert_eq!(game.next_profile_id, 2);
+        for game_type in [
+            GameType::Division,
+            GameType::DivisionWithRemainder,
+            GameType::FractionDivision,
+            GameType::DecimalDivision,
+        ] {
+            game.current_type = game_type;
+            for _ in 0..20 {
+                game.generate_problem();
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::Duration;
+
+// RoundStats sums up how long the answers of a round took
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundStats {
+    pub mean: Duration,
+    pub median: Duration,
+    pub fastest: Duration,
+    pub slowest: Duration,
+}
+
+impl RoundStats {
+    // of is None for a round without answers
+    pub fn of(latencies: &[Duration]) -> Option<Self> {
+        if latencies.is_empty() {
+            return None;
+        }
+        let mut sorted = latencies.to_vec();
+        sorted.sort();
+        let total = sorted
+            .iter()
+            .fold(Duration::zero(), |sum, latency| sum + *latency);
+        let middle = sorted.len() / 2;
+        // an even number of answers takes the mean of the two middle ones
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        } else {
+            sorted[middle]
+        };
+        Some(Self {
+            mean: total / sorted.len() as i32,
+            median,
+            fastest: sorted[0],
+            slowest: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_stats() {
+        let seconds =
+            |s: &[i64]| -> Vec<Duration> { s.iter().map(|s| Duration::seconds(*s)).collect() };
+        assert_eq!(RoundStats::of(&[]), None);
+
+        let stats = RoundStats::of(&seconds(&[4, 1, 10])).unwrap();
+        assert_eq!(stats.mean, Duration::seconds(5));
+        assert_eq!(stats.median, Duration::seconds(4));
+        assert_eq!(stats.fastest, Duration::seconds(1));
+        assert_eq!(stats.slowest, Duration::seconds(10));
+
+        let even = RoundStats::of(&seconds(&[2, 8, 4, 3])).unwrap();
+        assert_eq!(even.median, Duration::milliseconds(3500));
+    }
+}